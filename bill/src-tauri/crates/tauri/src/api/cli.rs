@@ -12,7 +12,7 @@ use crate::{
 use clap::{Arg, ArgMatches, ErrorKind};
 use serde::Serialize;
 use serde_json::Value;
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt};
 
 #[macro_use]
 mod macros;
@@ -38,6 +38,7 @@ use clapfix::{ClapCommand as App, ErrorExt};
 
 /// The resolution of a argument match.
 #[derive(Default, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
 #[non_exhaustive]
 pub struct ArgData {
   /// - [`Value::Bool`] if it's a flag,
@@ -48,6 +49,8 @@ pub struct ArgData {
   /// The number of occurrences of the argument.
   /// e.g. `./app --arg 1 --arg 2 --arg 2 3 4` results in three occurrences.
   pub occurrences: u64,
+  /// Whether the value is `-`, which by convention means the value must be read from stdin.
+  pub from_stdin: bool,
 }
 
 /// The matched subcommand.
@@ -80,6 +83,155 @@ impl Matches {
   pub(crate) fn set_subcommand(&mut self, name: String, matches: Matches) {
     self.subcommand = Some(Box::new(SubcommandMatches { name, matches }));
   }
+
+  /// Finds the argument matches of the subcommand with the given name among the matched subcommands,
+  /// e.g. `migrate` on `./app db migrate --dry-run`.
+  ///
+  /// Returns `None` if that subcommand was not used.
+  pub fn find_subcommand(&self, name: &str) -> Option<&Matches> {
+    let subcommand = self.subcommand.as_ref()?;
+    if subcommand.name == name {
+      Some(&subcommand.matches)
+    } else {
+      subcommand.matches.find_subcommand(name)
+    }
+  }
+
+  fn fmt_indented(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+    let indent = "  ".repeat(depth);
+    let mut names: Vec<&String> = self.args.keys().collect();
+    names.sort();
+    for name in names {
+      let arg = &self.args[name];
+      writeln!(
+        f,
+        "{}{}: {} (occurrences: {})",
+        indent, name, arg.value, arg.occurrences
+      )?;
+    }
+    if let Some(subcommand) = &self.subcommand {
+      writeln!(f, "{}{}:", indent, subcommand.name)?;
+      subcommand.matches.fmt_indented(f, depth + 1)?;
+    }
+    Ok(())
+  }
+}
+
+impl fmt::Display for Matches {
+  /// Formats the matches as an indented tree of args, followed by the matched subcommand path.
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    self.fmt_indented(f, 0)
+  }
+}
+
+/// Expands `@path` arguments by splicing the whitespace-separated arguments read from the file at `path`.
+///
+/// Argument files cannot reference other argument files.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use tauri::api::cli::expand_argfiles;
+/// // `./app @args.txt --verbose` where `args.txt` contains `--input file.txt`
+/// let args = expand_argfiles(std::env::args()).unwrap();
+/// ```
+pub fn expand_argfiles<I, T>(args: I) -> crate::api::Result<Vec<String>>
+where
+  I: IntoIterator<Item = T>,
+  T: Into<String>,
+{
+  let mut expanded = Vec::new();
+  for (i, arg) in args.into_iter().map(Into::into).enumerate() {
+    // the first argument is the binary name
+    match arg.strip_prefix('@') {
+      Some(path) if i > 0 => {
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+          crate::api::Error::ParseCliArguments(format!(
+            "failed to read argument file `{}`: {}",
+            path, e
+          ))
+        })?;
+        for file_arg in contents.split_whitespace() {
+          if file_arg.starts_with('@') {
+            return Err(crate::api::Error::ParseCliArguments(format!(
+              "argument file `{}` references another argument file `{}`",
+              path, file_arg
+            )));
+          }
+          expanded.push(file_arg.to_string());
+        }
+      }
+      _ => expanded.push(arg),
+    }
+  }
+  Ok(expanded)
+}
+
+/// Renders a version string from a template.
+///
+/// `{name}` and `{version}` are replaced with the package name and version,
+/// and `{key}` with the value of each `(key, value)` pair of `metadata`,
+/// e.g. a git hash or build date provided through `option_env!` at build time.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use tauri::api::cli::render_version;
+/// tauri::Builder::default()
+///   .setup(|app| {
+///     let version = render_version(
+///       "{name} {version} ({hash})",
+///       app.package_info(),
+///       &[("hash", option_env!("GIT_HASH").unwrap_or("unknown"))],
+///     );
+///     Ok(())
+///   });
+/// ```
+pub fn render_version(
+  template: &str,
+  package_info: &PackageInfo,
+  metadata: &[(&str, &str)],
+) -> String {
+  let mut rendered = template
+    .replace("{name}", &package_info.name)
+    .replace("{version}", &package_info.version);
+  for (key, value) in metadata {
+    rendered = rendered.replace(&format!("{{{}}}", key), value);
+  }
+  rendered
+}
+
+#[cfg(dev)]
+fn reloaded_config_store() -> &'static std::sync::Mutex<Option<CliConfig>> {
+  use once_cell::sync::Lazy;
+  static STORE: Lazy<std::sync::Mutex<Option<CliConfig>>> = Lazy::new(Default::default);
+  &STORE
+}
+
+/// Reads the CLI definition from the `tauri > cli` object of the given `tauri.conf.json` file.
+///
+/// The loaded definition is returned and also replaces the one used by the CLI endpoint,
+/// so the CLI schema can be changed without rebuilding the app.
+/// Only available on development builds (without the `custom-protocol` feature).
+#[cfg(dev)]
+pub fn reload_config<P: AsRef<std::path::Path>>(path: P) -> crate::api::Result<CliConfig> {
+  let config: Value = serde_json::from_slice(&std::fs::read(path)?)?;
+  let cli = config
+    .get("tauri")
+    .and_then(|tauri| tauri.get("cli"))
+    .cloned()
+    .ok_or_else(|| {
+      crate::api::Error::ParseCliArguments("CLI definition not set under tauri > cli".into())
+    })?;
+  let cli: CliConfig = serde_json::from_value(cli)?;
+  reloaded_config_store().lock().unwrap().replace(cli.clone());
+  Ok(cli)
+}
+
+/// The CLI definition loaded with [`reload_config`], if any.
+#[cfg(dev)]
+pub(crate) fn reloaded_config() -> Option<CliConfig> {
+  reloaded_config_store().lock().unwrap().clone()
 }
 
 /// Gets the argument matches of the CLI definition.
@@ -95,12 +247,44 @@ impl Matches {
 ///   });
 /// ```
 pub fn get_matches(cli: &CliConfig, package_info: &PackageInfo) -> crate::api::Result<Matches> {
+  get_matches_from(cli, package_info, std::env::args_os())
+}
+
+/// Gets the argument matches of the CLI definition, parsing `args` instead of the process arguments.
+///
+/// The first item of `args` is the binary name.
+/// Useful to handle the arguments of another app instance, e.g. forwarded to a single instance app.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use tauri::api::cli::get_matches_from;
+/// tauri::Builder::default()
+///   .setup(|app| {
+///     let matches = get_matches_from(
+///       app.config().tauri.cli.as_ref().unwrap(),
+///       app.package_info(),
+///       ["app", "--verbose"],
+///     )?;
+///     Ok(())
+///   });
+/// ```
+pub fn get_matches_from<I, T>(
+  cli: &CliConfig,
+  package_info: &PackageInfo,
+  args: I,
+) -> crate::api::Result<Matches>
+where
+  I: IntoIterator<Item = T>,
+  T: Into<std::ffi::OsString> + Clone,
+{
+  let args: Vec<std::ffi::OsString> = args.into_iter().map(Into::into).collect();
   let about = cli
     .description()
     .unwrap_or(&package_info.description.to_string())
     .to_string();
   let app = get_app(package_info, &package_info.name, Some(&about), cli);
-  match app.try_get_matches() {
+  match app.try_get_matches_from(&args) {
     Ok(matches) => Ok(get_matches_internal(cli, &matches)),
     Err(e) => match ErrorExt::kind(&e) {
       ErrorKind::DisplayHelp => {
@@ -110,7 +294,7 @@ pub fn get_matches(cli: &CliConfig, package_info: &PackageInfo) -> crate::api::R
           "help".to_string(),
           ArgData {
             value: Value::String(help_text),
-            occurrences: 0,
+            ..Default::default()
           },
         );
         Ok(matches)
@@ -122,23 +306,85 @@ pub fn get_matches(cli: &CliConfig, package_info: &PackageInfo) -> crate::api::R
           .insert("version".to_string(), Default::default());
         Ok(matches)
       }
+      ErrorKind::UnknownArgument => {
+        Err(subcommand_only_arg_error(cli, &args).unwrap_or_else(|| e.into()))
+      }
       _ => Err(e.into()),
     },
   }
 }
 
+/// Finds an argument used at the root level that is only defined on a subcommand.
+fn subcommand_only_arg_error(
+  cli: &CliConfig,
+  args: &[std::ffi::OsString],
+) -> Option<crate::api::Error> {
+  let subcommands = cli.subcommands()?;
+  let root_arg = |is_arg: &dyn Fn(&CliArg) -> bool| {
+    cli
+      .args()
+      .and_then(|args| args.iter().find(|arg| is_arg(arg)))
+  };
+  let subcommand_arg = |is_arg: &dyn Fn(&CliArg) -> bool| {
+    subcommands.iter().find_map(|(name, subcommand)| {
+      subcommand
+        .args()
+        .filter(|args| args.iter().any(|arg| is_arg(arg)))
+        .map(|_| name)
+    })
+  };
+  let error = |arg: String, subcommand: &str| {
+    crate::api::Error::ParseCliArguments(format!(
+      "argument `{}` can only be used with the `{}` subcommand",
+      arg, subcommand
+    ))
+  };
+
+  for arg in args.iter().skip(1) {
+    let arg = arg.to_string_lossy();
+    // stop at the first subcommand since its args are validated by clap
+    if subcommands.contains_key(arg.as_ref()) {
+      break;
+    }
+    if let Some(name) = arg.strip_prefix("--") {
+      let name = name.split('=').next().unwrap_or(name);
+      if root_arg(&|a| a.name == name).is_some() {
+        continue;
+      }
+      if let Some(subcommand) = subcommand_arg(&|a| a.name == name) {
+        return Some(error(format!("--{}", name), subcommand));
+      }
+    } else if let Some(shorts) = arg.strip_prefix('-') {
+      for short in shorts.chars() {
+        if let Some(root) = root_arg(&|a| a.short == Some(short)) {
+          // the rest of the cluster is the value of the argument
+          if root.takes_value.unwrap_or(false) {
+            break;
+          }
+          continue;
+        }
+        if let Some(subcommand) = subcommand_arg(&|a| a.short == Some(short)) {
+          return Some(error(format!("-{}", short), subcommand));
+        }
+      }
+    }
+  }
+
+  None
+}
+
 fn get_matches_internal(config: &CliConfig, matches: &ArgMatches) -> Matches {
   let mut cli_matches = Matches::default();
   map_matches(config, matches, &mut cli_matches);
 
   if let Some((subcommand_name, subcommand_matches)) = matches.subcommand() {
-    let mut subcommand_cli_matches = Matches::default();
-    map_matches(
-      config.subcommands().unwrap().get(subcommand_name).unwrap(),
-      subcommand_matches,
-      &mut subcommand_cli_matches,
+    cli_matches.set_subcommand(
+      subcommand_name.to_string(),
+      get_matches_internal(
+        config.subcommands().unwrap().get(subcommand_name).unwrap(),
+        subcommand_matches,
+      ),
     );
-    cli_matches.set_subcommand(subcommand_name.to_string(), subcommand_cli_matches);
   }
 
   cli_matches
@@ -168,7 +414,20 @@ fn map_matches(config: &CliConfig, matches: &ArgMatches, cli_matches: &mut Match
           .unwrap_or(Value::Null)
       };
 
-      cli_matches.set_arg(arg.name.clone(), ArgData { value, occurrences });
+      let from_stdin = match &value {
+        Value::String(v) => v == "-",
+        Value::Array(values) => values.iter().any(|v| v == "-"),
+        _ => false,
+      };
+
+      cli_matches.set_arg(
+        arg.name.clone(),
+        ArgData {
+          value,
+          occurrences,
+          from_stdin,
+        },
+      );
     }
   }
 }
@@ -265,3 +524,183 @@ fn get_arg<'a>(arg_name: &'a str, arg: &'a CliArg) -> Arg<'a> {
 
   clap_arg
 }
+
+#[cfg(test)]
+mod tests {
+  use super::{expand_argfiles, get_matches_from, render_version};
+  use crate::{utils::config::CliConfig, PackageInfo};
+
+  fn package_info() -> PackageInfo {
+    PackageInfo {
+      name: "test".into(),
+      version: "0.1.0".into(),
+      authors: "Tauri",
+      description: "Tauri test",
+    }
+  }
+
+  fn cli_config(config: serde_json::Value) -> CliConfig {
+    serde_json::from_value(config).expect("invalid CLI config")
+  }
+
+  #[test]
+  fn subcommand_only_arg() {
+    let cli = cli_config(serde_json::json!({
+      "args": [{ "name": "verbose" }],
+      "subcommands": {
+        "build": { "args": [{ "name": "release" }] }
+      }
+    }));
+
+    let matches = get_matches_from(&cli, &package_info(), ["test", "build", "--release"]).unwrap();
+    let subcommand = matches.subcommand.unwrap();
+    assert_eq!(subcommand.name, "build");
+    assert_eq!(subcommand.matches.args["release"].occurrences, 1);
+
+    match get_matches_from(&cli, &package_info(), ["test", "--release"]) {
+      Err(crate::api::Error::ParseCliArguments(message)) => {
+        assert!(message.contains("`--release`"));
+        assert!(message.contains("`build`"));
+      }
+      r => panic!("expected a CLI parse error, got {:?}", r),
+    }
+
+    let cli = cli_config(serde_json::json!({
+      "args": [{ "name": "verbose", "short": "v" }],
+      "subcommands": {
+        "build": { "args": [{ "name": "release", "short": "r" }] }
+      }
+    }));
+    for args in [["test", "-r"], ["test", "-vr"]] {
+      match get_matches_from(&cli, &package_info(), args) {
+        Err(crate::api::Error::ParseCliArguments(message)) => {
+          assert!(message.contains("`-r`"));
+          assert!(message.contains("`build`"));
+        }
+        r => panic!("expected a CLI parse error, got {:?}", r),
+      }
+    }
+  }
+
+  #[test]
+  fn stdin_value() {
+    let cli = cli_config(serde_json::json!({
+      "args": [{ "name": "input", "takesValue": true }]
+    }));
+
+    let matches = get_matches_from(&cli, &package_info(), ["test", "--input", "-"]).unwrap();
+    assert!(matches.args["input"].from_stdin);
+
+    let matches = get_matches_from(&cli, &package_info(), ["test", "--input", "file.txt"]).unwrap();
+    assert!(!matches.args["input"].from_stdin);
+  }
+
+  #[test]
+  fn display_matches() {
+    let cli = cli_config(serde_json::json!({
+      "args": [{ "name": "input", "takesValue": true }],
+      "subcommands": {
+        "build": { "args": [{ "name": "release" }] }
+      }
+    }));
+
+    let matches = get_matches_from(
+      &cli,
+      &package_info(),
+      ["test", "--input", "file.txt", "build", "--release"],
+    )
+    .unwrap();
+    let output = matches.to_string();
+    assert!(output.contains("input: \"file.txt\" (occurrences: 1)"));
+    assert!(output.contains("build:\n  release: true (occurrences: 1)"));
+  }
+
+  #[cfg(dev)]
+  #[test]
+  fn reload_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("tauri.conf.json");
+
+    std::fs::write(
+      &path,
+      r#"{ "tauri": { "cli": { "args": [{ "name": "verbose" }] } } }"#,
+    )
+    .unwrap();
+    let cli = super::reload_config(&path).unwrap();
+    assert!(get_matches_from(&cli, &package_info(), ["test", "--verbose"]).is_ok());
+
+    std::fs::write(
+      &path,
+      r#"{ "tauri": { "cli": { "args": [{ "name": "quiet" }] } } }"#,
+    )
+    .unwrap();
+    let cli = super::reload_config(&path).unwrap();
+    assert!(get_matches_from(&cli, &package_info(), ["test", "--verbose"]).is_err());
+    assert!(get_matches_from(&cli, &package_info(), ["test", "--quiet"]).is_ok());
+    assert!(super::reloaded_config().is_some());
+  }
+
+  #[test]
+  fn argfiles() {
+    let dir = tempfile::tempdir().unwrap();
+    let args_file = dir.path().join("args.txt");
+    std::fs::write(&args_file, "--input file.txt\n--verbose").unwrap();
+    let args_file = format!("@{}", args_file.display());
+
+    let args = expand_argfiles(["test", args_file.as_str(), "--quiet"]).unwrap();
+    assert_eq!(
+      args,
+      ["test", "--input", "file.txt", "--verbose", "--quiet"]
+    );
+
+    let cli = cli_config(serde_json::json!({
+      "args": [
+        { "name": "input", "takesValue": true },
+        { "name": "verbose" },
+        { "name": "quiet" }
+      ]
+    }));
+    let matches = get_matches_from(&cli, &package_info(), args).unwrap();
+    assert_eq!(matches.args["input"].value, "file.txt");
+    assert_eq!(matches.args["verbose"].value, true);
+
+    let nested = dir.path().join("nested.txt");
+    std::fs::write(&nested, args_file).unwrap();
+    assert!(expand_argfiles(["test".to_string(), format!("@{}", nested.display())]).is_err());
+
+    assert!(expand_argfiles(["test", "@missing-args.txt"]).is_err());
+  }
+
+  #[test]
+  fn version_template() {
+    assert_eq!(
+      render_version(
+        "{name} v{version} ({hash}, {date})",
+        &package_info(),
+        &[("hash", "abc1234")]
+      ),
+      "test v0.1.0 (abc1234, {date})"
+    );
+  }
+
+  #[test]
+  fn nested_subcommands() {
+    let cli = cli_config(serde_json::json!({
+      "subcommands": {
+        "db": {
+          "subcommands": {
+            "migrate": { "args": [{ "name": "dry-run" }] }
+          }
+        },
+        "status": {}
+      }
+    }));
+    let matches =
+      get_matches_from(&cli, &package_info(), ["app", "db", "migrate", "--dry-run"]).unwrap();
+
+    let migrate = matches.find_subcommand("migrate").unwrap();
+    assert_eq!(migrate.args["dry-run"].occurrences, 1);
+    assert!(matches.find_subcommand("db").is_some());
+    assert!(matches.find_subcommand("status").is_none());
+  }
+}
@@ -70,9 +70,13 @@ pub enum Error {
   #[error(transparent)]
   Zip(#[from] zip::result::ZipError),
   /// Notification error.
-  #[cfg(notification_all)]
+  #[cfg(feature = "notification")]
   #[error(transparent)]
   Notification(#[from] notify_rust::error::Error),
+  /// The user did not allow sending notifications.
+  #[cfg(feature = "notification")]
+  #[error("sending notification was not allowed by the user")]
+  NotificationNotAllowed,
   /// Url error.
   #[error(transparent)]
   Url(#[from] url::ParseError),
@@ -4,8 +4,750 @@
 
 //! Types and functions related to desktop notifications.
 
+use crate::{sealed::ManagerBase, AppHandle, Env, Manager, Runtime};
+
 #[cfg(windows)]
 use std::path::MAIN_SEPARATOR;
+use std::{
+  collections::HashMap,
+  fmt,
+  io::Write,
+  sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+  },
+};
+
+static SHOWN: AtomicU64 = AtomicU64::new(0);
+static FAILED: AtomicU64 = AtomicU64::new(0);
+static CLICKED: AtomicU64 = AtomicU64::new(0);
+static DISMISSED: AtomicU64 = AtomicU64::new(0);
+
+/// The maximum number of action buttons freedesktop notification servers commonly display.
+///
+/// Only Linux passes the actions to the platform, so the other platforms have no limit.
+#[cfg(all(unix, not(target_os = "macos")))]
+const MAX_ACTIONS: usize = 5;
+
+/// A snapshot of the notification delivery counters.
+///
+/// ## Platform-specific
+///
+/// - **Linux:** `clicked` and `dismissed` are best effort. They count the interactions the notification server reports
+///   on the session bus within 10 minutes of showing the notification, and none if the session bus can't be reached.
+/// - **Windows / macOS:** `clicked` and `dismissed` are always `0`, since notify-rust does not report the user interaction.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct NotificationMetrics {
+  /// Number of notifications successfully delivered to the platform.
+  pub shown: u64,
+  /// Number of notifications that failed to be delivered.
+  pub failed: u64,
+  /// Number of notifications activated by the user.
+  pub clicked: u64,
+  /// Number of notifications dismissed by the user.
+  pub dismissed: u64,
+}
+
+/// Gets the delivery counters of the notifications shown by this process.
+pub fn metrics() -> NotificationMetrics {
+  NotificationMetrics {
+    shown: SHOWN.load(Ordering::Relaxed),
+    failed: FAILED.load(Ordering::Relaxed),
+    clicked: CLICKED.load(Ordering::Relaxed),
+    dismissed: DISMISSED.load(Ordering::Relaxed),
+  }
+}
+
+/// Resets the notification delivery counters.
+pub fn reset_metrics() {
+  for counter in [&SHOWN, &FAILED, &CLICKED, &DISMISSED] {
+    counter.store(0, Ordering::Relaxed);
+  }
+}
+
+fn record_delivery(success: bool) {
+  let counter = if success { &SHOWN } else { &FAILED };
+  counter.fetch_add(1, Ordering::Relaxed);
+}
+
+#[cfg_attr(not(all(unix, not(target_os = "macos"))), allow(dead_code))]
+fn record_click() {
+  CLICKED.fetch_add(1, Ordering::Relaxed);
+}
+
+#[cfg_attr(not(all(unix, not(target_os = "macos"))), allow(dead_code))]
+fn record_dismiss() {
+  DISMISSED.fetch_add(1, Ordering::Relaxed);
+}
+
+#[cfg(test)]
+thread_local! {
+  /// The warnings of the current test.
+  static WARNINGS: std::cell::RefCell<Vec<String>> = Default::default();
+}
+
+/// Warns about a notification option the platform can't honor.
+///
+/// Emits a [`tracing`] warning with the `tracing` feature, and prints it to stderr on debug builds otherwise.
+fn warn(message: fmt::Arguments<'_>) {
+  #[cfg(feature = "tracing")]
+  tracing::warn!("{}", message);
+  #[cfg(all(debug_assertions, not(feature = "tracing")))]
+  eprintln!("{}", message);
+  #[cfg(test)]
+  WARNINGS.with(|warnings| warnings.borrow_mut().push(message.to_string()));
+}
+
+/// A category of notifications, e.g. `chat` or `updates`, that users can mute on platforms supporting it.
+///
+/// Register it with [`register_channel`] and assign notifications to it with [`Notification::channel`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelSpec {
+  /// The channel identifier.
+  pub id: String,
+  /// The user facing channel name.
+  pub name: String,
+  /// The freedesktop notification category, e.g. `im.received`. Defaults to the channel id.
+  pub category: Option<String>,
+}
+
+impl ChannelSpec {
+  /// Initializes a channel with the given identifier and name.
+  pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+    Self {
+      id: id.into(),
+      name: name.into(),
+      category: None,
+    }
+  }
+
+  /// Sets the freedesktop notification category of the channel.
+  #[must_use]
+  pub fn category(mut self, category: impl Into<String>) -> Self {
+    self.category = Some(category.into());
+    self
+  }
+}
+
+fn channels() -> &'static Mutex<HashMap<String, ChannelSpec>> {
+  use once_cell::sync::Lazy;
+  static CHANNELS: Lazy<Mutex<HashMap<String, ChannelSpec>>> = Lazy::new(Default::default);
+  &CHANNELS
+}
+
+/// Registers a notification channel, replacing any channel with the same identifier.
+pub fn register_channel(channel: ChannelSpec) {
+  channels()
+    .lock()
+    .unwrap()
+    .insert(channel.id.clone(), channel);
+}
+
+/// The notification category used for the given channel.
+#[cfg_attr(not(all(unix, not(target_os = "macos"))), allow(dead_code))]
+fn channel_category(id: &str) -> String {
+  match channels().lock().unwrap().get(id) {
+    Some(channel) => channel
+      .category
+      .clone()
+      .unwrap_or_else(|| channel.id.clone()),
+    None => {
+      warn(format_args!(
+        "notification channel `{}` is not registered",
+        id
+      ));
+      id.to_string()
+    }
+  }
+}
+
+/// The identity of the notification server, see [`server_info`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotificationServerInfo {
+  /// The product name of the server, e.g. `dunst`.
+  pub name: String,
+  /// The vendor name.
+  pub vendor: String,
+  /// The server version.
+  pub version: String,
+  /// The version of the notification specification the server is compliant with.
+  pub spec_version: String,
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+impl From<notify_rust::ServerInformation> for NotificationServerInfo {
+  fn from(info: notify_rust::ServerInformation) -> Self {
+    Self {
+      name: info.name,
+      vendor: info.vendor,
+      version: info.version,
+      spec_version: info.spec_version,
+    }
+  }
+}
+
+/// Queries the notification server handling the notifications of the current session.
+///
+/// ## Platform-specific
+///
+/// - **Linux:** Calls the freedesktop `GetServerInformation` method, returning `None` if no server is reachable.
+/// - **Windows / macOS:** Unsupported, always returns `None`.
+pub fn server_info() -> Option<NotificationServerInfo> {
+  #[cfg(all(unix, not(target_os = "macos")))]
+  return notify_rust::get_server_information().ok().map(Into::into);
+  #[cfg(not(all(unix, not(target_os = "macos"))))]
+  None
+}
+
+/// The action identifier reported when the notification body is clicked.
+///
+/// Register an action with this identifier to label the default action.
+pub const DEFAULT_ACTION: &str = "default";
+
+/// The action dispatched when the notification is closed without an action.
+#[cfg_attr(not(all(unix, not(target_os = "macos"))), allow(dead_code))]
+const CLOSED_ACTION: &str = "__closed";
+
+/// Called with the identifier of the action the user invoked and the notification metadata.
+struct ActionCallback(Box<dyn FnOnce(String, serde_json::Value) + Send>);
+
+impl fmt::Debug for ActionCallback {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("ActionCallback").finish()
+  }
+}
+
+/// Called with the notification metadata when the notification is closed without an action.
+struct CloseCallback(Box<dyn FnOnce(serde_json::Value) + Send>);
+
+impl fmt::Debug for CloseCallback {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("CloseCallback").finish()
+  }
+}
+
+/// The functions called when the user interacts with the notification.
+#[derive(Debug, Default)]
+struct Callbacks {
+  on_action: Option<ActionCallback>,
+  on_close: Option<CloseCallback>,
+  /// The [`Notification::metadata`], passed to the callbacks.
+  metadata: serde_json::Value,
+}
+
+impl Callbacks {
+  /// Runs the callback matching the action the notification server reported, recording it on the metrics.
+  #[cfg_attr(not(all(unix, not(target_os = "macos"))), allow(dead_code))]
+  fn dispatch(self, action: &str) {
+    if action == CLOSED_ACTION {
+      record_dismiss();
+      if let Some(on_close) = self.on_close {
+        (on_close.0)(self.metadata);
+      }
+    } else {
+      record_click();
+      if let Some(on_action) = self.on_action {
+        (on_action.0)(action.to_string(), self.metadata);
+      }
+    }
+  }
+}
+
+/// What a delivered notification keeps until the user interacts with it.
+#[cfg_attr(not(all(unix, not(target_os = "macos"))), allow(dead_code))]
+#[derive(Debug)]
+struct Pending {
+  callbacks: Callbacks,
+  /// The temporary [`Notification::icon_bytes`] file, deleted when dropped.
+  icon_file: Option<tempfile::TempPath>,
+}
+
+/// Hands notifications to the platform. Replaced by a fake in the tests, so they don't display notifications.
+trait Platform {
+  /// Shows the notification.
+  fn show(
+    &self,
+    notification: &notify_rust::Notification,
+  ) -> crate::api::Result<NotificationHandle>;
+
+  /// Keeps the callbacks and the icon file of the shown notification until the user interacts with it.
+  fn listen(&self, id: Option<u32>, pending: Pending);
+}
+
+/// The notify-rust platform.
+struct Native;
+
+impl Platform for Native {
+  fn show(
+    &self,
+    notification: &notify_rust::Notification,
+  ) -> crate::api::Result<NotificationHandle> {
+    let handle = notification.show()?;
+    #[cfg(all(unix, not(target_os = "macos")))]
+    return Ok(NotificationHandle {
+      id: Some(handle.id()),
+      inner: Some(handle),
+    });
+    #[cfg(not(all(unix, not(target_os = "macos"))))]
+    {
+      let _ = handle;
+      Ok(NotificationHandle::empty())
+    }
+  }
+
+  fn listen(&self, id: Option<u32>, pending: Pending) {
+    #[cfg(all(unix, not(target_os = "macos")))]
+    if let Some(id) = id {
+      listener::listen(id, pending);
+    }
+    // the user interaction is not reported, so the icon file is kept for a while
+    #[cfg(not(all(unix, not(target_os = "macos"))))]
+    if let Some(icon_file) = pending.icon_file {
+      crate::async_runtime::spawn(async move {
+        tokio::time::sleep(ICON_FILE_LIFETIME).await;
+        drop(icon_file);
+      });
+    }
+  }
+}
+
+/// The notifications waiting for the user to interact with them, by platform identifier,
+/// with the instant they are forgotten.
+#[cfg_attr(not(all(unix, not(target_os = "macos"))), allow(dead_code))]
+#[derive(Debug, Default)]
+struct PendingNotifications(HashMap<u32, (std::time::Instant, Pending)>);
+
+#[cfg_attr(not(all(unix, not(target_os = "macos"))), allow(dead_code))]
+impl PendingNotifications {
+  /// Adds a notification forgotten after `timeout`.
+  ///
+  /// A notification replacing another one with the same identifier, e.g. with the same [`Notification::tag`],
+  /// supersedes it: the callbacks of the replaced notification are dropped, so only the displayed one is dispatched.
+  fn insert(&mut self, id: u32, pending: Pending, timeout: std::time::Duration) {
+    self.expire();
+    self
+      .0
+      .insert(id, (std::time::Instant::now() + timeout, pending));
+  }
+
+  /// Removes the notification, to dispatch the user interaction.
+  fn take(&mut self, id: u32) -> Option<Pending> {
+    self.0.remove(&id).map(|(_, pending)| pending)
+  }
+
+  /// Forgets the expired notifications, dropping their callbacks and icon files.
+  fn expire(&mut self) {
+    let now = std::time::Instant::now();
+    self.0.retain(|_, (expires, _)| *expires > now);
+  }
+}
+
+/// Listens to the freedesktop notification signals on the session bus, running the callbacks of the notifications.
+#[cfg(all(unix, not(target_os = "macos")))]
+mod listener {
+  use super::{Pending, PendingNotifications, CLOSED_ACTION};
+  use once_cell::sync::Lazy;
+  use std::{
+    sync::{Mutex, Once},
+    time::Duration,
+  };
+
+  /// How long the user interaction with a notification is waited for.
+  ///
+  /// Notifications the server never closes, e.g. resident ones or the ones kept on a history panel,
+  /// are forgotten after it, so their callbacks and icon files don't accumulate.
+  const TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+  /// The notifications waiting for the user, by server identifier.
+  static PENDING: Lazy<Mutex<PendingNotifications>> = Lazy::new(Default::default);
+
+  /// Waits for the user to interact with the notification with the given server identifier.
+  ///
+  /// The listener thread is started with the first notification.
+  /// A notification replacing another one with the same identifier drops its callbacks.
+  pub(super) fn listen(id: u32, pending: Pending) {
+    static START: Once = Once::new();
+    START.call_once(|| {
+      if let Err(e) = std::thread::Builder::new()
+        .name("tauri-notification-listener".into())
+        .spawn(run)
+      {
+        eprintln!("failed to start the notification listener: {}", e);
+      }
+    });
+    PENDING.lock().unwrap().insert(id, pending, TIMEOUT);
+  }
+
+  /// Runs the callbacks of the notification if it is still waiting, then deletes its icon file.
+  fn dispatch(id: u32, action: &str) {
+    // the callbacks run without the lock, since they may show notifications
+    let pending = PENDING.lock().unwrap().take(id);
+    if let Some(pending) = pending {
+      pending.callbacks.dispatch(action);
+    }
+  }
+
+  fn run() {
+    if let Err(e) = listen_signals() {
+      eprintln!("failed to listen to the notification signals: {}", e);
+    }
+  }
+
+  fn listen_signals() -> Result<(), dbus::Error> {
+    use dbus::{blocking::Connection, message::MatchRule};
+
+    let connection = Connection::new_session()?;
+    connection.add_match(
+      MatchRule::new_signal("org.freedesktop.Notifications", "ActionInvoked"),
+      |(id, action): (u32, String), _, _| {
+        dispatch(id, &action);
+        true
+      },
+    )?;
+    // also sent after an action is invoked, when the notification is already dispatched
+    connection.add_match(
+      MatchRule::new_signal("org.freedesktop.Notifications", "NotificationClosed"),
+      |(id, _reason): (u32, u32), _, _| {
+        dispatch(id, CLOSED_ACTION);
+        true
+      },
+    )?;
+    loop {
+      connection.process(Duration::from_secs(60))?;
+      PENDING.lock().unwrap().expire();
+    }
+  }
+}
+
+/// The last notification shown with a tag.
+#[cfg_attr(not(all(unix, not(target_os = "macos"))), allow(dead_code))]
+#[derive(Debug, Default)]
+struct TagSlot {
+  /// The platform identifier of the last notification shown, replaced by the next one.
+  id: Option<u32>,
+  /// The sequence number of the last notification reserved.
+  reserved: u64,
+  /// The sequence number of the last notification shown, which owns `id`.
+  ///
+  /// Its callbacks are registered while the slot is locked, so they supersede the ones of the notifications it replaced.
+  shown: u64,
+}
+
+/// The delivery slot of each tag.
+fn tags() -> &'static Mutex<HashMap<String, Arc<Mutex<TagSlot>>>> {
+  use once_cell::sync::Lazy;
+  static TAGS: Lazy<Mutex<HashMap<String, Arc<Mutex<TagSlot>>>>> = Lazy::new(Default::default);
+  &TAGS
+}
+
+/// The position of a tagged notification in the tag order, reserved before it is handed to a delivery task.
+#[cfg_attr(not(all(unix, not(target_os = "macos"))), allow(dead_code))]
+#[derive(Debug)]
+struct TagReservation {
+  slot: Arc<Mutex<TagSlot>>,
+  seq: u64,
+}
+
+#[cfg_attr(not(all(unix, not(target_os = "macos"))), allow(dead_code))]
+impl TagReservation {
+  /// Reserves the next position of the tag.
+  fn new(tag: &str) -> Self {
+    let slot = tags()
+      .lock()
+      .unwrap()
+      .entry(tag.to_string())
+      .or_default()
+      .clone();
+    let seq = {
+      let mut slot = slot.lock().unwrap();
+      slot.reserved += 1;
+      slot.reserved
+    };
+    Self { slot, seq }
+  }
+
+  /// Calls `show` with the identifier of the last notification shown with the tag, so it is replaced.
+  ///
+  /// Notifications with the same tag are shown one at a time. A notification is skipped,
+  /// returning `None`, if one reserved after it was already shown.
+  fn show<T>(
+    &self,
+    show: impl FnOnce(Option<u32>) -> crate::api::Result<(u32, T)>,
+  ) -> crate::api::Result<Option<T>> {
+    let mut slot = self.slot.lock().unwrap();
+    if slot.shown > self.seq {
+      return Ok(None);
+    }
+    let (id, output) = show(slot.id)?;
+    slot.id = Some(id);
+    slot.shown = self.seq;
+    Ok(Some(output))
+  }
+
+  /// Calls `show` with the identifier of the last notification shown with the tag, so it is replaced.
+  ///
+  /// Unlike [`Self::show`], the notification is also skipped if no notification was shown with the tag.
+  fn replace<T>(
+    &self,
+    show: impl FnOnce(u32) -> crate::api::Result<(u32, T)>,
+  ) -> crate::api::Result<Option<T>> {
+    let mut slot = self.slot.lock().unwrap();
+    let replaces = match slot.id {
+      Some(id) if slot.shown <= self.seq => id,
+      _ => return Ok(None),
+    };
+    let (id, output) = show(replaces)?;
+    slot.id = Some(id);
+    slot.shown = self.seq;
+    Ok(Some(output))
+  }
+}
+
+/// A notification delivered with [`Notification::show_blocking`].
+pub struct NotificationHandle {
+  id: Option<u32>,
+  /// The notify-rust handle, used to close the notification.
+  #[cfg(all(unix, not(target_os = "macos")))]
+  inner: Option<notify_rust::NotificationHandle>,
+}
+
+impl fmt::Debug for NotificationHandle {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("NotificationHandle")
+      .field("id", &self.id)
+      .finish()
+  }
+}
+
+impl NotificationHandle {
+  fn empty() -> Self {
+    Self {
+      id: None,
+      #[cfg(all(unix, not(target_os = "macos")))]
+      inner: None,
+    }
+  }
+
+  /// The platform notification identifier.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / macOS:** Unsupported, always `None`.
+  pub fn id(&self) -> Option<u32> {
+    self.id
+  }
+
+  /// Closes the notification.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / macOS:** Unsupported, returns `Ok(())` without effect.
+  pub fn close(self) -> crate::api::Result<()> {
+    #[cfg(all(unix, not(target_os = "macos")))]
+    if let Some(inner) = self.inner {
+      inner.close();
+    }
+    Ok(())
+  }
+}
+
+/// A notification scheduled with [`Notification::schedule`].
+#[derive(Debug, Clone)]
+pub struct ScheduledNotification(Arc<crate::async_runtime::JoinHandle<()>>);
+
+impl ScheduledNotification {
+  /// Cancels the notification if it was not shown yet. Does nothing otherwise.
+  pub fn cancel(&self) {
+    self.0.abort();
+  }
+}
+
+/// Whether the user allows the app to show notifications.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PermissionState {
+  /// The user allowed notifications.
+  Granted,
+  /// The user denied notifications.
+  Denied,
+  /// The user was not asked yet.
+  Default,
+  /// The permission can't be determined, for example because the user can't be prompted.
+  Unknown,
+}
+
+impl From<Option<bool>> for PermissionState {
+  fn from(allow_notification: Option<bool>) -> Self {
+    match allow_notification {
+      Some(true) => Self::Granted,
+      Some(false) => Self::Denied,
+      None => Self::Default,
+    }
+  }
+}
+
+/// Reads the notification permission stored on the app settings.
+fn stored_permission<R: Runtime>(app: &AppHandle<R>) -> PermissionState {
+  crate::settings::read_settings(
+    &app.config(),
+    app.package_info(),
+    app.state::<Env>().inner(),
+  )
+  .allow_notification
+  .into()
+}
+
+/// Unwraps the API error of a failed settings update.
+#[cfg(feature = "dialog")]
+fn settings_error(error: crate::Error) -> crate::api::Error {
+  match error {
+    crate::Error::FailedToExecuteApi(e) => e,
+    crate::Error::Io(e) => crate::api::Error::Io(e),
+    e => crate::api::Error::Io(std::io::Error::new(
+      std::io::ErrorKind::Other,
+      e.to_string(),
+    )),
+  }
+}
+
+/// Checks the notification permission when the notification is shown.
+#[derive(Clone)]
+struct PermissionCheck(Arc<dyn Fn() -> PermissionState + Send + Sync>);
+
+impl fmt::Debug for PermissionCheck {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("PermissionCheck").finish()
+  }
+}
+
+/// Checks whether the app currently has a focused window.
+#[derive(Clone)]
+struct FocusCheck(Arc<dyn Fn() -> bool + Send + Sync>);
+
+impl fmt::Debug for FocusCheck {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("FocusCheck").finish()
+  }
+}
+
+/// The encoding of a notification icon given as bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ImageFormat {
+  /// A PNG image.
+  Png,
+  /// A JPEG image.
+  Jpeg,
+  /// An ICO image.
+  Ico,
+  /// A BMP image.
+  Bmp,
+}
+
+impl ImageFormat {
+  /// The file extension of the format.
+  fn extension(self) -> &'static str {
+    match self {
+      Self::Png => "png",
+      Self::Jpeg => "jpg",
+      Self::Ico => "ico",
+      Self::Bmp => "bmp",
+    }
+  }
+}
+
+/// The urgency level of a notification.
+///
+/// ## Platform-specific
+///
+/// - **Windows / macOS:** Unsupported, the urgency is ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Urgency {
+  /// Low urgency, e.g. background information.
+  Low,
+  /// Normal urgency.
+  Normal,
+  /// Critical urgency, usually not dismissed automatically.
+  Critical,
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+impl From<Urgency> for notify_rust::Urgency {
+  fn from(urgency: Urgency) -> Self {
+    match urgency {
+      Urgency::Low => Self::Low,
+      Urgency::Normal => Self::Normal,
+      Urgency::Critical => Self::Critical,
+    }
+  }
+}
+
+/// How long a notification stays on screen.
+///
+/// ## Platform-specific
+///
+/// - **Windows:** Only distinguishes [`Timeout::Never`], which keeps the toast longer, from the other values.
+/// - **macOS:** Unsupported, the timeout is ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Timeout {
+  /// The platform default timeout.
+  Default,
+  /// The notification stays until the user dismisses it.
+  Never,
+  /// The notification is closed after the given number of milliseconds.
+  Milliseconds(u32),
+}
+
+impl From<Timeout> for notify_rust::Timeout {
+  fn from(timeout: Timeout) -> Self {
+    match timeout {
+      Timeout::Default => Self::Default,
+      Timeout::Never => Self::Never,
+      Timeout::Milliseconds(ms) => Self::Milliseconds(ms),
+    }
+  }
+}
+
+/// The sound played when a notification is shown.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Sound {
+  /// The platform default notification sound.
+  Default,
+  /// Do not play any sound.
+  Silent,
+  /// A sound identified by its name, e.g. a [freedesktop sound name](http://0pointer.de/public/sound-naming-spec.html) on Linux.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** One of the toast sounds `Default`, `IM`, `Mail`, `Reminder` or `SMS`.
+  ///   Other names fall back to the default sound.
+  Named(String),
+}
+
+/// The sounds of the Windows toast audio element.
+#[cfg(windows)]
+const WINDOWS_SOUNDS: &[&str] = &["Default", "IM", "Mail", "Reminder", "SMS"];
+
+/// The name of a [`Sound::Named`] sound the platform can play, or `None` to play the default sound.
+fn supported_sound_name(name: &str) -> Option<&str> {
+  #[cfg(windows)]
+  {
+    let sound = WINDOWS_SOUNDS
+      .iter()
+      .find(|sound| sound.eq_ignore_ascii_case(name))
+      .copied();
+    if sound.is_none() {
+      warn(format_args!(
+        "notification sound `{}` is not a toast sound; falling back to the default sound",
+        name
+      ));
+    }
+    sound
+  }
+  #[cfg(not(windows))]
+  Some(name)
+}
 
 /// The desktop notification definition.
 ///
@@ -38,8 +780,32 @@ pub struct Notification {
   title: Option<String>,
   /// The notification icon.
   icon: Option<String>,
+  /// The encoded notification icon, written to a temporary file when shown.
+  icon_bytes: Option<(Vec<u8>, ImageFormat)>,
   /// The notification identifier
   identifier: String,
+  /// The notification urgency.
+  urgency: Option<Urgency>,
+  /// The notification timeout.
+  timeout: Option<Timeout>,
+  /// The notification sound.
+  sound: Option<Sound>,
+  /// Suppresses the notification while the app has a focused window.
+  focus_check: Option<FocusCheck>,
+  /// Fails to show the notification if the user denied notifications.
+  permission_check: Option<PermissionCheck>,
+  /// The notification action buttons, as `(identifier, label)` pairs.
+  actions: Vec<(String, String)>,
+  /// Called when an action is invoked or the notification is closed.
+  callbacks: Callbacks,
+  /// Keeps the notification open when an action is invoked.
+  resident: bool,
+  /// The identifier of the channel the notification belongs to.
+  channel: Option<String>,
+  /// Replaces the last notification shown with the same tag.
+  tag: Option<String>,
+  /// The progress percentage and its label.
+  progress: Option<(u8, Option<String>)>,
 }
 
 impl Notification {
@@ -51,6 +817,105 @@ impl Notification {
     }
   }
 
+  /// Gets the notification permission the user gave to the app.
+  ///
+  /// The answer is stored on the app settings, the same as the permission requested by the JS API.
+  pub fn permission_state<R: Runtime>(app: &AppHandle<R>) -> crate::api::Result<PermissionState> {
+    Ok(stored_permission(app))
+  }
+
+  /// Asks the user to allow notifications if they were not asked yet, calling `f` with the resulting permission.
+  ///
+  /// The prompt doesn't block the current thread, so this can be called on the main thread,
+  /// such as in the `setup` hook or the tray and menu event handlers.
+  /// The answer is stored on the app settings before `f` is called, and `f` gets the error if it can't be written.
+  ///
+  /// Without the `dialog` feature the user can't be prompted, so `f` gets [`PermissionState::Unknown`].
+  pub fn request_permission<R: Runtime, F>(app: &AppHandle<R>, f: F)
+  where
+    F: FnOnce(crate::api::Result<PermissionState>) + Send + 'static,
+  {
+    #[allow(unused_mut)]
+    let mut settings = crate::settings::read_settings(
+      &app.config(),
+      app.package_info(),
+      app.state::<Env>().inner(),
+    );
+    if settings.allow_notification.is_some() {
+      return f(Ok(settings.allow_notification.into()));
+    }
+    #[cfg(feature = "dialog")]
+    {
+      let app = app.clone();
+      crate::api::dialog::ask(
+        None::<&crate::Window<R>>,
+        "Permissions",
+        "This app wants to show notifications. Do you allow?",
+        move |answer| {
+          settings.allow_notification = Some(answer);
+          f(crate::settings::write_settings(
+            &app.config(),
+            app.package_info(),
+            app.state::<Env>().inner(),
+            settings,
+          )
+          .map(|()| Some(answer).into())
+          .map_err(settings_error))
+        },
+      );
+    }
+    #[cfg(not(feature = "dialog"))]
+    f(Ok(PermissionState::Unknown))
+  }
+
+  /// Initializes an error notification with the given title.
+  ///
+  /// Uses [`Urgency::Critical`], the `dialog-error` icon and the default sound.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / macOS:** The icon is a freedesktop icon theme name, so no icon is set.
+  pub fn error(identifier: impl Into<String>, title: impl Into<String>) -> Self {
+    Self::new(identifier)
+      .title(title)
+      .severity(Urgency::Critical, "dialog-error", Sound::Default)
+  }
+
+  /// Initializes a warning notification with the given title.
+  ///
+  /// Uses [`Urgency::Normal`], the `dialog-warning` icon and the default sound.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / macOS:** The icon is a freedesktop icon theme name, so no icon is set.
+  pub fn warning(identifier: impl Into<String>, title: impl Into<String>) -> Self {
+    Self::new(identifier)
+      .title(title)
+      .severity(Urgency::Normal, "dialog-warning", Sound::Default)
+  }
+
+  /// Initializes an informational notification with the given title.
+  ///
+  /// Uses [`Urgency::Low`], the `dialog-information` icon and no sound.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / macOS:** The icon is a freedesktop icon theme name, so no icon is set.
+  pub fn info(identifier: impl Into<String>, title: impl Into<String>) -> Self {
+    Self::new(identifier)
+      .title(title)
+      .severity(Urgency::Low, "dialog-information", Sound::Silent)
+  }
+
+  /// Sets the urgency, the icon theme name and the sound of a severity constructor.
+  #[allow(unused_variables)]
+  fn severity(self, urgency: Urgency, icon: &str, sound: Sound) -> Self {
+    let notification = self.urgency(urgency).sound(sound);
+    #[cfg(all(unix, not(target_os = "macos")))]
+    let notification = notification.icon(icon);
+    notification
+  }
+
   /// Sets the notification body.
   #[must_use]
   pub fn body(mut self, body: impl Into<String>) -> Self {
@@ -69,20 +934,341 @@ impl Notification {
   #[must_use]
   pub fn icon(mut self, icon: impl Into<String>) -> Self {
     self.icon = Some(icon.into());
+    self.icon_bytes = None;
     self
   }
 
-  /// Shows the notification.
-  pub fn show(self) -> crate::api::Result<()> {
+  /// Sets the notification icon from the encoded image, e.g. a resource embedded with `include_bytes!`.
+  ///
+  /// The image is written to a temporary file when the notification is shown.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** the file is deleted once the notification is closed, or 10 minutes after it is shown.
+  /// - **Windows / macOS:** the file is deleted a minute after the notification is shown,
+  ///   since the app is not told when it closes.
+  #[must_use]
+  pub fn icon_bytes(mut self, bytes: Vec<u8>, format: ImageFormat) -> Self {
+    self.icon_bytes = Some((bytes, format));
+    self.icon = None;
+    self
+  }
+
+  /// Appends action buttons to the notification, as `(identifier, label)` pairs.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** Actions beyond the first 5 are discarded, since notification servers don't display more.
+  /// - **Windows / macOS:** Unsupported, the actions are not displayed.
+  #[must_use]
+  pub fn actions<I: IntoIterator<Item = (String, String)>>(mut self, actions: I) -> Self {
+    self.actions.extend(actions);
+    #[cfg(all(unix, not(target_os = "macos")))]
+    if self.actions.len() > MAX_ACTIONS {
+      warn(format_args!(
+        "notification has {} actions but the platform only supports {}; discarding the remaining actions",
+        self.actions.len(),
+        MAX_ACTIONS
+      ));
+      self.actions.truncate(MAX_ACTIONS);
+    }
+    self
+  }
+
+  /// Appends an action button to the notification.
+  ///
+  /// Use [`DEFAULT_ACTION`] as the identifier to set the action of clicking the notification body.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** Actions beyond the first 5 are discarded, since notification servers don't display more.
+  /// - **Windows / macOS:** Unsupported, the actions are not displayed.
+  #[must_use]
+  pub fn action(self, id: impl Into<String>, label: impl Into<String>) -> Self {
+    self.actions(std::iter::once((id.into(), label.into())))
+  }
+
+  /// Sets a function called with the identifier of the action the user invoked.
+  ///
+  /// Clicking the notification body reports [`DEFAULT_ACTION`].
+  /// The function is not called if the notification is closed without an action.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** The function is dropped without being called if the user doesn't interact with the notification
+  ///   within 10 minutes of showing it.
+  /// - **Windows / macOS:** Unsupported, the function is never called.
+  #[must_use]
+  pub fn on_action<F: FnOnce(String) + Send + 'static>(self, f: F) -> Self {
+    self.on_action_with_metadata(move |action, _| f(action))
+  }
+
+  /// Sets a function called with the identifier of the action the user invoked and the [`Self::metadata`].
+  ///
+  /// The metadata is [`serde_json::Value::Null`] if none is set. See [`Self::on_action`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** The function is dropped without being called if the user doesn't interact with the notification
+  ///   within 10 minutes of showing it.
+  /// - **Windows / macOS:** Unsupported, the function is never called.
+  #[must_use]
+  pub fn on_action_with_metadata<F: FnOnce(String, serde_json::Value) + Send + 'static>(
+    mut self,
+    f: F,
+  ) -> Self {
+    self.callbacks.on_action = Some(ActionCallback(Box::new(f)));
+    self
+  }
+
+  /// Sets a function called when the user dismisses the notification or it expires.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** The function is dropped without being called if the notification is not closed
+  ///   within 10 minutes of showing it.
+  /// - **Windows / macOS:** Unsupported, the function is never called.
+  #[must_use]
+  pub fn on_close<F: FnOnce() + Send + 'static>(self, f: F) -> Self {
+    self.on_close_with_metadata(move |_| f())
+  }
+
+  /// Sets a function called with the [`Self::metadata`] when the user dismisses the notification or it expires.
+  ///
+  /// The metadata is [`serde_json::Value::Null`] if none is set.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** The function is dropped without being called if the notification is not closed
+  ///   within 10 minutes of showing it.
+  /// - **Windows / macOS:** Unsupported, the function is never called.
+  #[must_use]
+  pub fn on_close_with_metadata<F: FnOnce(serde_json::Value) + Send + 'static>(
+    mut self,
+    f: F,
+  ) -> Self {
+    self.callbacks.on_close = Some(CloseCallback(Box::new(f)));
+    self
+  }
+
+  /// Attaches a value to the notification, e.g. the record it refers to,
+  /// passed to the [`Self::on_action_with_metadata`] and [`Self::on_close_with_metadata`] functions.
+  ///
+  /// The value stays in the app, it is not sent to the notification server.
+  #[must_use]
+  pub fn metadata(mut self, metadata: serde_json::Value) -> Self {
+    self.callbacks.metadata = metadata;
+    self
+  }
+
+  /// Sets the notification urgency.
+  #[must_use]
+  pub fn urgency(mut self, urgency: Urgency) -> Self {
+    self.urgency = Some(urgency);
+    self
+  }
+
+  /// Sets how long the notification stays on screen.
+  #[must_use]
+  pub fn timeout(mut self, timeout: Timeout) -> Self {
+    self.timeout = Some(timeout);
+    self
+  }
+
+  /// Sets the notification sound.
+  #[must_use]
+  pub fn sound(mut self, sound: Sound) -> Self {
+    self.sound = Some(sound);
+    self
+  }
+
+  /// Keeps the notification open after one of its actions is invoked, e.g. for media controls.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** Sets the freedesktop `resident` hint, which the notification server may ignore.
+  /// - **Windows / macOS:** Unsupported, the platform closes the notification when it is activated.
+  #[must_use]
+  pub fn resident(mut self, resident: bool) -> Self {
+    self.resident = resident;
+    self
+  }
+
+  /// Assigns the notification to a channel registered with [`register_channel`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** Sets the freedesktop `category` hint to the channel category.
+  /// - **Windows / macOS:** Unsupported, the channel is ignored.
+  #[must_use]
+  pub fn channel(mut self, id: impl Into<String>) -> Self {
+    self.channel = Some(id.into());
+    self
+  }
+
+  /// Sets a tag, so showing this notification replaces the last notification shown with the same tag
+  /// instead of stacking a new one, e.g. to update a download progress.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** Reuses the server-assigned identifier of the previous notification.
+  /// - **Windows / macOS:** Unsupported, a new notification is shown next to the previous one.
+  #[must_use]
+  pub fn tag(mut self, tag: impl Into<String>) -> Self {
+    self.tag = Some(tag.into());
+    self
+  }
+
+  /// Displays the progress of an operation, as a percentage clamped to `100`.
+  ///
+  /// Combine with [`Self::tag`] to update the progress of the same notification,
+  /// each update replacing the ones shown before it.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** Sets the freedesktop `value` hint, which the notification server may ignore, and adds the label to the body.
+  /// - **Windows / macOS:** Unsupported, the body displays the label and the percentage instead, e.g. `Syncing — 42%`.
+  #[must_use]
+  pub fn progress(mut self, value: u8, label: Option<String>) -> Self {
+    self.progress = Some((value.min(100), label));
+    self
+  }
+
+  /// Only shows the notification if none of the app windows has focus when [`Self::show`] is called.
+  ///
+  /// Useful to avoid notifying the user about something they are already looking at.
+  #[must_use]
+  pub fn only_when_backgrounded<R: Runtime>(mut self, app: &AppHandle<R>, enabled: bool) -> Self {
+    self.focus_check = if enabled {
+      let manager = app.manager().clone();
+      Some(FocusCheck(Arc::new(move || manager.is_focused())))
+    } else {
+      None
+    };
+    self
+  }
+
+  /// Makes [`Self::show`] fail with [`crate::api::Error::NotificationNotAllowed`] if the user denied notifications.
+  ///
+  /// Useful to fall back to an in-app message. The permission is checked when the notification is shown.
+  #[must_use]
+  pub fn require_permission<R: Runtime>(mut self, app: &AppHandle<R>) -> Self {
+    let app = app.clone();
+    self.permission_check = Some(PermissionCheck(Arc::new(move || stored_permission(&app))));
+    self
+  }
+
+  /// Whether the notification must be skipped because the app is in the foreground.
+  fn is_suppressed(&self) -> bool {
+    self
+      .focus_check
+      .as_ref()
+      .map(|check| (check.0)())
+      .unwrap_or(false)
+  }
+
+  /// The body, followed by the progress text on platforms that have no progress bar.
+  fn body_text(&self) -> Option<String> {
+    let progress = self.progress.as_ref().and_then(|(value, label)| {
+      if cfg!(all(unix, not(target_os = "macos"))) {
+        label.clone()
+      } else {
+        Some(match label {
+          Some(label) => format!("{} — {}%", label, value),
+          None => format!("{}%", value),
+        })
+      }
+    });
+    match (&self.body, progress) {
+      (Some(body), Some(progress)) => Some(format!("{}\n{}", body, progress)),
+      (body, progress) => progress.or_else(|| body.clone()),
+    }
+  }
+
+  /// Converts the definition into the notify-rust notification.
+  fn native(&self) -> notify_rust::Notification {
     let mut notification = notify_rust::Notification::new();
-    if let Some(body) = self.body {
+    if let Some(body) = self.body_text() {
       notification.body(&body);
     }
-    if let Some(title) = self.title {
-      notification.summary(&title);
+    if let Some(title) = &self.title {
+      notification.summary(title);
+    }
+    if let Some(icon) = &self.icon {
+      notification.icon(icon);
+    }
+    #[cfg(all(unix, not(target_os = "macos")))]
+    if let Some(urgency) = self.urgency {
+      notification.urgency(urgency.into());
+    }
+    #[cfg(not(target_os = "macos"))]
+    if let Some(timeout) = self.timeout {
+      notification.timeout(timeout);
+    }
+    #[cfg(all(unix, not(target_os = "macos")))]
+    for (identifier, label) in &self.actions {
+      notification.action(identifier, label);
+    }
+    #[cfg(all(unix, not(target_os = "macos")))]
+    if let Some((value, _)) = self.progress {
+      notification.hint(notify_rust::Hint::CustomInt("value".into(), value.into()));
+    }
+    #[cfg(all(unix, not(target_os = "macos")))]
+    if self.resident {
+      notification.hint(notify_rust::Hint::Resident(true));
     }
-    if let Some(icon) = self.icon {
-      notification.icon(&icon);
+    #[cfg(all(unix, not(target_os = "macos")))]
+    if let Some(channel) = &self.channel {
+      notification.hint(notify_rust::Hint::Category(channel_category(channel)));
+    }
+    match &self.sound {
+      Some(Sound::Silent) => {
+        #[cfg(all(unix, not(target_os = "macos")))]
+        notification.hint(notify_rust::Hint::SuppressSound(true));
+        // a name that is not a toast sound removes the toast audio element
+        #[cfg(windows)]
+        notification.sound_name("Silent");
+      }
+      Some(Sound::Named(name)) => {
+        if let Some(name) = supported_sound_name(name) {
+          notification.sound_name(name);
+        }
+      }
+      Some(Sound::Default) | None => {}
+    }
+    notification
+  }
+
+  /// Writes the [`Self::icon_bytes`] image to a temporary file, deleted when the returned path is dropped.
+  fn icon_file(&self) -> crate::api::Result<Option<tempfile::TempPath>> {
+    match &self.icon_bytes {
+      Some((bytes, format)) => {
+        let mut file = tempfile::Builder::new()
+          .suffix(&format!(".{}", format.extension()))
+          .tempfile()?;
+        file.write_all(bytes)?;
+        Ok(Some(file.into_temp_path()))
+      }
+      None => Ok(None),
+    }
+  }
+
+  /// Builds the platform notification and its icon file, or `None` if it must not be shown.
+  fn prepare(
+    &self,
+  ) -> crate::api::Result<Option<(notify_rust::Notification, Option<tempfile::TempPath>)>> {
+    if let Some(check) = &self.permission_check {
+      if (check.0)() == PermissionState::Denied {
+        return Err(crate::api::Error::NotificationNotAllowed);
+      }
+    }
+    if self.is_suppressed() {
+      return Ok(None);
+    }
+    let mut notification = self.native();
+    let icon_file = self.icon_file()?;
+    if let Some(path) = &icon_file {
+      notification.icon(&path.to_string_lossy());
     }
     #[cfg(windows)]
     {
@@ -96,11 +1282,748 @@ impl Notification {
         notification.app_id(&self.identifier);
       }
     }
+    Ok(Some((notification, icon_file)))
+  }
 
+  /// Shows the notification without waiting for it to be delivered.
+  ///
+  /// Delivery errors are printed to stderr. Use [`Self::show_blocking`] to handle them.
+  pub fn show(self) -> crate::api::Result<()> {
+    let (notification, icon_file) = match self.prepare()? {
+      Some(prepared) => prepared,
+      None => return Ok(()),
+    };
+    let identifier = self.identifier;
+    // reserved before spawning, so notifications with the same tag replace each other in `show` order
+    let tag = self.tag.as_deref().map(TagReservation::new);
+    let pending = Pending {
+      callbacks: self.callbacks,
+      icon_file,
+    };
     crate::async_runtime::spawn(async move {
-      notification.show().expect("failed to show notification");
+      if let Err(e) = deliver(&identifier, notification, tag, pending, &Native) {
+        eprintln!("failed to show notification: {}", e);
+      }
     });
-
     Ok(())
   }
+
+  /// Shows the notification after the given delay, without blocking the current thread.
+  ///
+  /// The returned handle can cancel the notification before it is shown.
+  pub fn schedule(self, delay: std::time::Duration) -> ScheduledNotification {
+    ScheduledNotification(Arc::new(crate::async_runtime::spawn(async move {
+      tokio::time::sleep(delay).await;
+      if let Err(e) = self.show() {
+        eprintln!("failed to show notification: {}", e);
+      }
+    })))
+  }
+
+  /// Shows the notification, blocking the current thread until it is delivered to the platform.
+  ///
+  /// A notification suppressed by [`Self::only_when_backgrounded`] returns a handle without identifier.
+  pub fn show_blocking(self) -> crate::api::Result<NotificationHandle> {
+    self.show_blocking_with(&Native)
+  }
+
+  /// Updates the content of the last notification shown with the same [`Self::tag`] without alerting the user again,
+  /// e.g. to update a progress. Blocks the current thread until the update is delivered.
+  ///
+  /// Does nothing, returning a handle without identifier, if the notification has no tag
+  /// or no notification with its tag was shown. Silent updates are not counted as shown on the [`metrics`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** Replaces the notification with the `suppress-sound` hint set.
+  ///   The notification server decides whether the replaced notification pops up again.
+  /// - **Windows / macOS:** Unsupported, does nothing.
+  pub fn update_silent(self) -> crate::api::Result<NotificationHandle> {
+    self.update_silent_with(&Native)
+  }
+
+  /// Silently updates the notification on the given platform, see [`Self::update_silent`].
+  #[allow(unused_variables)]
+  fn update_silent_with(self, platform: &impl Platform) -> crate::api::Result<NotificationHandle> {
+    #[cfg(all(unix, not(target_os = "macos")))]
+    if let Some(tag) = &self.tag {
+      let (mut notification, icon_file) = match self.prepare()? {
+        Some(prepared) => prepared,
+        None => return Ok(NotificationHandle::empty()),
+      };
+      notification.hint(notify_rust::Hint::SuppressSound(true));
+      let handle = TagReservation::new(tag).replace(|replaces| {
+        notification.id(replaces);
+        let result = platform.show(&notification);
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+          operation = "update_silent",
+          identifier = %self.identifier,
+          result = ?result.as_ref().map(|_| ()),
+          "notification"
+        );
+        if result.is_err() {
+          record_delivery(false);
+        }
+        let handle = result?;
+        // listening with the tag locked, see `deliver`
+        platform.listen(
+          handle.id(),
+          Pending {
+            callbacks: self.callbacks,
+            icon_file,
+          },
+        );
+        Ok((handle.id().unwrap_or(replaces), handle))
+      })?;
+      if let Some(handle) = handle {
+        return Ok(handle);
+      }
+    }
+    Ok(NotificationHandle::empty())
+  }
+
+  /// Shows the notification on the given platform, blocking the current thread until it is delivered.
+  fn show_blocking_with(self, platform: &impl Platform) -> crate::api::Result<NotificationHandle> {
+    match self.prepare()? {
+      Some((notification, icon_file)) => deliver(
+        &self.identifier,
+        notification,
+        self.tag.as_deref().map(TagReservation::new),
+        Pending {
+          callbacks: self.callbacks,
+          icon_file,
+        },
+        platform,
+      ),
+      None => Ok(NotificationHandle::empty()),
+    }
+  }
+}
+
+/// How long the icon file of a notification is kept on platforms that don't report when it closes.
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+const ICON_FILE_LIFETIME: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Hands the notification to the platform, recording the delivery result.
+///
+/// The platform then keeps the callbacks and the icon file until the user interacts with the notification.
+#[allow(unused_variables, unused_mut)]
+fn deliver(
+  identifier: &str,
+  mut notification: notify_rust::Notification,
+  tag: Option<TagReservation>,
+  pending: Pending,
+  platform: &impl Platform,
+) -> crate::api::Result<NotificationHandle> {
+  // the other platforms can't replace a notification, so tagged notifications are all shown
+  #[cfg(not(all(unix, not(target_os = "macos"))))]
+  let tag: Option<TagReservation> = None;
+  let mut show = |replaces: Option<u32>| {
+    #[cfg(all(unix, not(target_os = "macos")))]
+    if let Some(id) = replaces {
+      notification.id(id);
+    }
+    let result = platform.show(&notification);
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+      operation = "show",
+      identifier = %identifier,
+      result = ?result.as_ref().map(|_| ()),
+      "notification"
+    );
+    record_delivery(result.is_ok());
+    result
+  };
+  match &tag {
+    // listening with the tag locked, so the callbacks of a replaced notification can't supersede these ones
+    Some(tag) => Ok(
+      tag
+        .show(|replaces| {
+          let handle = show(replaces)?;
+          platform.listen(handle.id(), pending);
+          Ok((handle.id().unwrap_or_default(), handle))
+        })?
+        // a notification shown later with the same tag is already displayed
+        .unwrap_or_else(NotificationHandle::empty),
+    ),
+    None => {
+      let handle = show(None)?;
+      platform.listen(handle.id(), pending);
+      Ok(handle)
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{FocusCheck, ImageFormat, Notification, ScheduledNotification, Sound, Urgency};
+  use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+  };
+
+  /// Serializes the tests that update the global metrics counters.
+  static METRICS_LOCK: once_cell::sync::Lazy<Mutex<()>> =
+    once_cell::sync::Lazy::new(Default::default);
+
+  /// Records the notifications instead of displaying them.
+  #[derive(Default)]
+  struct FakePlatform {
+    fail: bool,
+    shown: Mutex<Vec<notify_rust::Notification>>,
+    pending: Mutex<Vec<(Option<u32>, super::Pending)>>,
+  }
+
+  impl super::Platform for FakePlatform {
+    fn show(
+      &self,
+      notification: &notify_rust::Notification,
+    ) -> crate::api::Result<super::NotificationHandle> {
+      if self.fail {
+        return Err(
+          std::io::Error::new(std::io::ErrorKind::Other, "no notification server").into(),
+        );
+      }
+      let mut shown = self.shown.lock().unwrap();
+      shown.push(notification.clone());
+      Ok(super::NotificationHandle {
+        id: Some(shown.len() as u32),
+        #[cfg(all(unix, not(target_os = "macos")))]
+        inner: None,
+      })
+    }
+
+    fn listen(&self, id: Option<u32>, pending: super::Pending) {
+      self.pending.lock().unwrap().push((id, pending));
+    }
+  }
+
+  #[test]
+  fn only_when_backgrounded() {
+    let focused = Arc::new(AtomicBool::new(true));
+    let focused_ = focused.clone();
+    let mut notification = Notification::new("com.tauri.test");
+    notification.focus_check = Some(FocusCheck(Arc::new(move || {
+      focused_.load(Ordering::SeqCst)
+    })));
+
+    assert!(notification.is_suppressed());
+    focused.store(false, Ordering::SeqCst);
+    assert!(!notification.is_suppressed());
+
+    assert!(!Notification::new("com.tauri.test").is_suppressed());
+  }
+
+  #[test]
+  fn suppressed_show_blocking() {
+    let mut notification = Notification::new("com.tauri.test");
+    notification.focus_check = Some(FocusCheck(Arc::new(|| true)));
+    assert!(notification.prepare().unwrap().is_none());
+    assert_eq!(notification.show_blocking().unwrap().id(), None);
+  }
+
+  #[test]
+  fn severity_constructors() {
+    // the icon theme names only resolve on Linux
+    let icon = |name| {
+      if cfg!(all(unix, not(target_os = "macos"))) {
+        Some(name)
+      } else {
+        None
+      }
+    };
+
+    let error = Notification::error("com.tauri.test", "Error");
+    assert_eq!(error.title.as_deref(), Some("Error"));
+    assert_eq!(error.urgency, Some(Urgency::Critical));
+    assert_eq!(error.icon.as_deref(), icon("dialog-error"));
+    assert_eq!(error.sound, Some(Sound::Default));
+
+    let warning = Notification::warning("com.tauri.test", "Warning").body("details");
+    assert_eq!(warning.urgency, Some(Urgency::Normal));
+    assert_eq!(warning.icon.as_deref(), icon("dialog-warning"));
+    assert_eq!(warning.sound, Some(Sound::Default));
+    assert_eq!(warning.body.as_deref(), Some("details"));
+
+    let info = Notification::info("com.tauri.test", "Info");
+    assert_eq!(info.urgency, Some(Urgency::Low));
+    assert_eq!(info.icon.as_deref(), icon("dialog-information"));
+    assert_eq!(info.sound, Some(Sound::Silent));
+  }
+
+  #[test]
+  fn metrics() {
+    let _lock = METRICS_LOCK.lock().unwrap();
+    let before = super::metrics();
+    let platform = FakePlatform::default();
+    let handle = Notification::new("com.tauri.test")
+      .title("Metrics")
+      .show_blocking_with(&platform)
+      .unwrap();
+    assert_eq!(handle.id(), Some(1));
+    let failing = FakePlatform {
+      fail: true,
+      ..Default::default()
+    };
+    assert!(Notification::new("com.tauri.test")
+      .show_blocking_with(&failing)
+      .is_err());
+    assert!(failing.pending.lock().unwrap().is_empty());
+    let after = super::metrics();
+    assert_eq!(after.shown - before.shown, 1);
+    assert_eq!(after.failed - before.failed, 1);
+
+    // the user dismisses the notification shown on the fake platform
+    let before = super::metrics();
+    let (id, pending) = platform.pending.lock().unwrap().remove(0);
+    assert_eq!(id, Some(1));
+    pending.callbacks.dispatch(super::CLOSED_ACTION);
+    // interactions are counted without callbacks too
+    super::Callbacks::default().dispatch(super::DEFAULT_ACTION);
+    let after = super::metrics();
+    assert_eq!(after.clicked - before.clicked, 1);
+    assert_eq!(after.dismissed - before.dismissed, 1);
+  }
+
+  #[cfg(all(unix, not(target_os = "macos")))]
+  #[test]
+  fn actions_are_truncated() {
+    let notification = Notification::new("com.tauri.test")
+      .actions((0..super::MAX_ACTIONS + 2).map(|i| (i.to_string(), format!("Action {}", i))));
+    assert_eq!(notification.actions.len(), super::MAX_ACTIONS);
+    assert_eq!(notification.actions[0], ("0".into(), "Action 0".into()));
+    super::WARNINGS.with(|warnings| {
+      assert_eq!(
+        *warnings.borrow(),
+        vec![format!(
+          "notification has {} actions but the platform only supports {}; discarding the remaining actions",
+          super::MAX_ACTIONS + 2,
+          super::MAX_ACTIONS
+        )]
+      )
+    });
+  }
+
+  #[test]
+  fn resident() {
+    let notification = Notification::new("com.tauri.test").resident(true);
+    assert!(notification.resident);
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+      use notify_rust::Hint;
+      assert!(notification.native().hints.contains(&Hint::Resident(true)));
+      assert!(!Notification::new("com.tauri.test")
+        .native()
+        .hints
+        .contains(&Hint::Resident(true)));
+    }
+  }
+
+  #[test]
+  fn channels() {
+    super::register_channel(super::ChannelSpec::new("chat", "Chat").category("im.received"));
+    super::register_channel(super::ChannelSpec::new("updates", "Updates"));
+    assert_eq!(super::channel_category("chat"), "im.received");
+    assert_eq!(super::channel_category("updates"), "updates");
+
+    let notification = Notification::new("com.tauri.test").channel("chat");
+    assert_eq!(notification.channel.as_deref(), Some("chat"));
+    #[cfg(all(unix, not(target_os = "macos")))]
+    assert!(notification
+      .native()
+      .hints
+      .contains(&notify_rust::Hint::Category("im.received".into())));
+  }
+
+  #[cfg(all(unix, not(target_os = "macos")))]
+  #[test]
+  fn server_info() {
+    let info: super::NotificationServerInfo = notify_rust::ServerInformation {
+      name: "dunst".into(),
+      vendor: "knopwob".into(),
+      version: "1.9.0".into(),
+      spec_version: "1.2".into(),
+    }
+    .into();
+    assert_eq!(info.name, "dunst");
+    assert_eq!(info.vendor, "knopwob");
+    assert_eq!(info.version, "1.9.0");
+    assert_eq!(info.spec_version, "1.2");
+  }
+
+  #[test]
+  fn action_callback() {
+    let _lock = METRICS_LOCK.lock().unwrap();
+    let notification = Notification::new("com.tauri.test")
+      .action(super::DEFAULT_ACTION, "Open")
+      .action("reply", "Reply");
+    assert_eq!(
+      notification.actions,
+      vec![
+        ("default".into(), "Open".into()),
+        ("reply".into(), "Reply".into())
+      ]
+    );
+
+    let invoked = Arc::new(Mutex::new(Vec::new()));
+    let callbacks = |invoked: &Arc<Mutex<Vec<String>>>| {
+      let invoked = invoked.clone();
+      let closed = invoked.clone();
+      super::Callbacks {
+        on_action: Some(super::ActionCallback(Box::new(move |action, _| {
+          invoked.lock().unwrap().push(action)
+        }))),
+        on_close: Some(super::CloseCallback(Box::new(move |_| {
+          closed.lock().unwrap().push("closed".into())
+        }))),
+        ..Default::default()
+      }
+    };
+    let before = super::metrics();
+    callbacks(&invoked).dispatch(super::DEFAULT_ACTION);
+    callbacks(&invoked).dispatch("reply");
+    callbacks(&invoked).dispatch(super::CLOSED_ACTION);
+    assert_eq!(*invoked.lock().unwrap(), vec!["default", "reply", "closed"]);
+    let after = super::metrics();
+    assert_eq!(after.clicked - before.clicked, 2);
+    assert_eq!(after.dismissed - before.dismissed, 1);
+  }
+
+  #[test]
+  fn metadata() {
+    let _lock = METRICS_LOCK.lock().unwrap();
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let show = |platform: &FakePlatform, metadata: Option<serde_json::Value>| {
+      let on_action = received.clone();
+      let on_close = received.clone();
+      let mut notification = Notification::new("com.tauri.test")
+        .on_action_with_metadata(move |action, metadata| {
+          on_action.lock().unwrap().push((action, metadata))
+        })
+        .on_close_with_metadata(move |metadata| {
+          on_close.lock().unwrap().push(("closed".into(), metadata))
+        });
+      if let Some(metadata) = metadata {
+        notification = notification.metadata(metadata);
+      }
+      notification.show_blocking_with(platform).unwrap();
+    };
+    let platform = FakePlatform::default();
+    show(&platform, Some(serde_json::json!({ "record": 42 })));
+    show(&platform, Some(serde_json::json!("draft")));
+    show(&platform, None);
+
+    // the user activates the first notification and dismisses the others
+    let mut pending = platform.pending.lock().unwrap();
+    pending
+      .remove(0)
+      .1
+      .callbacks
+      .dispatch(super::DEFAULT_ACTION);
+    for (_, pending) in pending.drain(..) {
+      pending.callbacks.dispatch(super::CLOSED_ACTION);
+    }
+    assert_eq!(
+      *received.lock().unwrap(),
+      vec![
+        ("default".into(), serde_json::json!({ "record": 42 })),
+        ("closed".into(), serde_json::json!("draft")),
+        ("closed".into(), serde_json::Value::Null),
+      ]
+    );
+  }
+
+  #[test]
+  fn timeout() {
+    use super::Timeout;
+
+    let notification = Notification::new("com.tauri.test")
+      .body("x")
+      .timeout(Timeout::Never)
+      .urgency(Urgency::Critical);
+    assert_eq!(notification.timeout, Some(Timeout::Never));
+    assert_eq!(notification.urgency, Some(Urgency::Critical));
+
+    for (timeout, expected) in [
+      (Timeout::Default, notify_rust::Timeout::Default),
+      (Timeout::Never, notify_rust::Timeout::Never),
+      (
+        Timeout::Milliseconds(500),
+        notify_rust::Timeout::Milliseconds(500),
+      ),
+    ] {
+      assert_eq!(notify_rust::Timeout::from(timeout), expected);
+    }
+    #[cfg(all(unix, not(target_os = "macos")))]
+    assert_eq!(notification.native().timeout, notify_rust::Timeout::Never);
+  }
+
+  #[test]
+  fn tag() {
+    use super::TagReservation;
+
+    let notification = Notification::new("com.tauri.test").tag("download");
+    assert_eq!(notification.tag.as_deref(), Some("download"));
+
+    // returns whether the notification was shown and the identifier it replaced
+    let show = |reservation: &TagReservation, id: u32| {
+      let mut replaced = None;
+      let shown = reservation
+        .show(|previous| {
+          replaced = previous;
+          Ok((id, ()))
+        })
+        .unwrap();
+      (shown.is_some(), replaced)
+    };
+    // reserved in `show` order, delivered in any order
+    let first = TagReservation::new("tag-test");
+    let second = TagReservation::new("tag-test");
+    assert_eq!(show(&second, 1), (true, None));
+    // the older notification must not replace the newer one
+    assert_eq!(show(&first, 2), (false, None));
+    assert_eq!(show(&TagReservation::new("tag-test"), 3), (true, Some(1)));
+    assert_eq!(
+      show(&TagReservation::new("other-tag-test"), 4),
+      (true, None)
+    );
+  }
+
+  #[cfg(all(unix, not(target_os = "macos")))]
+  #[test]
+  fn update_silent() {
+    let _lock = METRICS_LOCK.lock().unwrap();
+    let platform = FakePlatform::default();
+    let progress = |value| {
+      Notification::new("com.tauri.test")
+        .tag("silent-test")
+        .progress(value, Some("Syncing".into()))
+    };
+
+    // there is nothing to update yet
+    let handle = progress(10).update_silent_with(&platform).unwrap();
+    assert_eq!(handle.id(), None);
+    assert!(Notification::new("com.tauri.test")
+      .update_silent_with(&platform)
+      .unwrap()
+      .id()
+      .is_none());
+    assert!(platform.shown.lock().unwrap().is_empty());
+
+    progress(20).show_blocking_with(&platform).unwrap();
+    let before = super::metrics();
+    let handle = progress(30).update_silent_with(&platform).unwrap();
+    assert_eq!(handle.id(), Some(2));
+    let after = super::metrics();
+    assert_eq!(after.shown, before.shown);
+    assert_eq!(after.failed, before.failed);
+
+    let shown = platform.shown.lock().unwrap();
+    assert_eq!(shown.len(), 2);
+    assert!(!shown[0]
+      .hints
+      .contains(&notify_rust::Hint::SuppressSound(true)));
+    assert!(shown[1]
+      .hints
+      .contains(&notify_rust::Hint::SuppressSound(true)));
+  }
+
+  #[test]
+  fn superseded_callbacks() {
+    use std::time::Duration;
+
+    let _lock = METRICS_LOCK.lock().unwrap();
+    let invoked = Arc::new(Mutex::new(Vec::new()));
+    let pending = |name: &'static str| {
+      let invoked = invoked.clone();
+      super::Pending {
+        callbacks: super::Callbacks {
+          on_action: Some(super::ActionCallback(Box::new(move |action, _| {
+            invoked.lock().unwrap().push((name, action))
+          }))),
+          ..Default::default()
+        },
+        icon_file: None,
+      }
+    };
+
+    let mut notifications = super::PendingNotifications::default();
+    // a tagged notification replaces the one with the same server identifier
+    notifications.insert(7, pending("first"), Duration::from_secs(60));
+    notifications.insert(7, pending("second"), Duration::from_secs(60));
+    let before = super::metrics();
+    notifications
+      .take(7)
+      .unwrap()
+      .callbacks
+      .dispatch(super::DEFAULT_ACTION);
+    assert!(notifications.take(7).is_none());
+    assert_eq!(
+      *invoked.lock().unwrap(),
+      vec![("second", super::DEFAULT_ACTION.to_string())]
+    );
+    assert_eq!(super::metrics().clicked - before.clicked, 1);
+
+    notifications.insert(8, pending("expired"), Duration::ZERO);
+    notifications.expire();
+    assert!(notifications.take(8).is_none());
+  }
+
+  #[test]
+  fn progress() {
+    let notification = Notification::new("com.tauri.test")
+      .body("Uploading files")
+      .progress(142, Some("Syncing".into()));
+    assert_eq!(notification.progress, Some((100, Some("Syncing".into()))));
+
+    let native = notification.native();
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+      assert!(native
+        .hints
+        .contains(&notify_rust::Hint::CustomInt("value".into(), 100)));
+      assert_eq!(native.body, "Uploading files\nSyncing");
+      let native = Notification::new("com.tauri.test")
+        .progress(42, None)
+        .native();
+      assert!(native
+        .hints
+        .contains(&notify_rust::Hint::CustomInt("value".into(), 42)));
+      assert_eq!(native.body, "");
+    }
+    #[cfg(not(all(unix, not(target_os = "macos"))))]
+    {
+      assert_eq!(native.body, "Uploading files\nSyncing — 100%");
+      let native = Notification::new("com.tauri.test")
+        .progress(42, None)
+        .native();
+      assert_eq!(native.body, "42%");
+    }
+  }
+
+  #[test]
+  fn schedule() {
+    use std::time::Duration;
+
+    // the focus check runs when the scheduled notification is due, and suppresses it
+    let scheduled = |fired: &Arc<AtomicBool>, delay: Duration| {
+      let fired = fired.clone();
+      let mut notification = Notification::new("com.tauri.test");
+      notification.focus_check = Some(FocusCheck(Arc::new(move || {
+        fired.store(true, Ordering::SeqCst);
+        true
+      })));
+      notification.schedule(delay)
+    };
+    let wait = |scheduled: ScheduledNotification| {
+      crate::async_runtime::block_on(Arc::try_unwrap(scheduled.0).unwrap())
+    };
+
+    let fired = Arc::new(AtomicBool::new(false));
+    assert!(wait(scheduled(&fired, Duration::ZERO)).is_ok());
+    assert!(fired.load(Ordering::SeqCst));
+
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let handle = scheduled(&cancelled, Duration::from_secs(3600));
+    handle.clone().cancel();
+    assert!(wait(handle).is_err());
+    assert!(!cancelled.load(Ordering::SeqCst));
+  }
+
+  #[test]
+  fn sound_fallback() {
+    use super::supported_sound_name;
+
+    #[cfg(windows)]
+    {
+      assert_eq!(supported_sound_name("mail"), Some("Mail"));
+      assert_eq!(supported_sound_name("message-new-instant"), None);
+    }
+    #[cfg(not(windows))]
+    assert_eq!(
+      supported_sound_name("message-new-instant"),
+      Some("message-new-instant")
+    );
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+      let silent = Notification::new("com.tauri.test")
+        .sound(Sound::Silent)
+        .native();
+      assert!(silent
+        .hints
+        .contains(&notify_rust::Hint::SuppressSound(true)));
+      let named = Notification::new("com.tauri.test")
+        .sound(Sound::Named("bell".into()))
+        .native();
+      assert!(named
+        .hints
+        .contains(&notify_rust::Hint::SoundName("bell".into())));
+    }
+  }
+
+  #[test]
+  fn permission() {
+    use super::{PermissionCheck, PermissionState};
+
+    assert_eq!(PermissionState::from(Some(true)), PermissionState::Granted);
+    assert_eq!(PermissionState::from(Some(false)), PermissionState::Denied);
+    assert_eq!(PermissionState::from(None), PermissionState::Default);
+
+    let with_permission = |state: PermissionState| {
+      let mut notification = Notification::new("com.tauri.test");
+      notification.permission_check = Some(PermissionCheck(Arc::new(move || state)));
+      // keeps the notification from being delivered
+      notification.focus_check = Some(FocusCheck(Arc::new(|| true)));
+      notification
+    };
+    assert!(matches!(
+      with_permission(PermissionState::Denied).show_blocking(),
+      Err(crate::api::Error::NotificationNotAllowed)
+    ));
+    assert!(with_permission(PermissionState::Granted)
+      .show_blocking()
+      .is_ok());
+    assert!(with_permission(PermissionState::Default)
+      .show_blocking()
+      .is_ok());
+    assert!(with_permission(PermissionState::Unknown)
+      .show_blocking()
+      .is_ok());
+  }
+
+  #[test]
+  fn icon_bytes() {
+    let notification = Notification::new("com.tauri.test")
+      .icon("dialog-information")
+      .icon_bytes(vec![137, 80, 78, 71], ImageFormat::Png);
+    assert!(notification.icon.is_none());
+
+    let (_, icon_file) = notification.prepare().unwrap().unwrap();
+    let icon_file = icon_file.unwrap();
+    let path = icon_file.to_path_buf();
+    assert_eq!(path.extension().unwrap(), "png");
+    assert_eq!(std::fs::read(&path).unwrap(), vec![137, 80, 78, 71]);
+    drop(icon_file);
+    assert!(!path.exists());
+
+    // the icon file outlives the delivery, the platform reads it when displaying the notification
+    let _lock = METRICS_LOCK.lock().unwrap();
+    let platform = FakePlatform::default();
+    Notification::new("com.tauri.test")
+      .icon_bytes(vec![137, 80, 78, 71], ImageFormat::Png)
+      .show_blocking_with(&platform)
+      .unwrap();
+    let path = std::path::PathBuf::from(&platform.shown.lock().unwrap()[0].icon);
+    assert!(path.exists());
+    // until the notification is closed
+    drop(platform);
+    assert!(!path.exists());
+
+    let notification = Notification::new("com.tauri.test")
+      .icon_bytes(vec![1], ImageFormat::Png)
+      .icon("dialog-information");
+    assert!(notification.prepare().unwrap().unwrap().1.is_none());
+  }
 }
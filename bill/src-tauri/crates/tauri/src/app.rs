@@ -407,6 +407,8 @@ impl<R: Runtime> AppHandle<R> {
     {
       let _ = self.remove_system_tray();
     }
+    #[cfg(all(target_os = "linux", feature = "system-tray"))]
+    tray::remove_icon_files();
   }
 }
 
@@ -1369,50 +1371,49 @@ impl<R: Runtime> Builder<R> {
       if let Some(menu) = system_tray.menu() {
         tray::get_menu_ids(&mut ids, menu);
       }
-      let mut tray = tray::SystemTray::new();
-      if let Some(menu) = system_tray.menu {
+      let icon = system_tray
+        .icon
+        .or(system_tray_icon)
+        .expect("tray icon not found; please configure it on tauri.conf.json");
+      let mut tray = tray::SystemTray::new().with_icon(icon.clone());
+      if let Some(menu) = system_tray.menu.clone() {
         tray = tray.with_menu(menu);
       }
 
-      #[cfg(not(target_os = "macos"))]
-      let tray_handler = app
-        .runtime
-        .as_ref()
-        .unwrap()
-        .system_tray(
-          tray.with_icon(
-            system_tray
-              .icon
-              .or(system_tray_icon)
-              .expect("tray icon not found; please configure it on tauri.conf.json"),
-          ),
-        )
-        .expect("failed to run tray");
-
       #[cfg(target_os = "macos")]
+      let tray = tray.with_icon_as_template(system_tray_icon_as_template);
+
       let tray_handler = app
         .runtime
         .as_ref()
         .unwrap()
-        .system_tray(
-          tray
-            .with_icon(
-              system_tray
-                .icon
-                .or(system_tray_icon)
-                .expect("tray icon not found; please configure it on tauri.conf.json"),
-            )
-            .with_icon_as_template(system_tray_icon_as_template),
-        )
+        .system_tray(tray)
         .expect("failed to run tray");
 
       let tray_handle = tray::SystemTrayHandle {
         ids: Arc::new(std::sync::Mutex::new(ids)),
+        state: Arc::new(std::sync::Mutex::new(tray::TrayState {
+          icon: Some(icon),
+          menu: system_tray.menu,
+          ..Default::default()
+        })),
         inner: tray_handler,
+        #[cfg(windows)]
+        runtime_handle: app.handle.runtime_handle.clone(),
       };
       let ids = tray_handle.ids.clone();
+      let state = tray_handle.state.clone();
       app.tray_handle.replace(tray_handle.clone());
       app.handle.tray_handle.replace(tray_handle);
+      {
+        let app_handle = app.handle();
+        let ids = ids.clone();
+        app
+          .runtime
+          .as_mut()
+          .unwrap()
+          .on_system_tray_event(move |event| tray::on_tray_event(&app_handle, &ids, &state, event));
+      }
       for listener in self.system_tray_event_listeners {
         let app_handle = app.handle();
         let ids = ids.clone();
@@ -1424,9 +1425,14 @@ impl<R: Runtime> Builder<R> {
           .on_system_tray_event(move |event| {
             let app_handle = app_handle.clone();
             let event = match event {
-              RuntimeSystemTrayEvent::MenuItemClick(id) => tray::SystemTrayEvent::MenuItemClick {
-                id: ids.lock().unwrap().get(id).unwrap().clone(),
-              },
+              RuntimeSystemTrayEvent::MenuItemClick(id) => {
+                // the item may have been removed from the menu since it was clicked
+                let id = ids.lock().unwrap().get(id).cloned();
+                match id {
+                  Some(id) => tray::SystemTrayEvent::MenuItemClick { id },
+                  None => return,
+                }
+              }
               RuntimeSystemTrayEvent::LeftClick { position, size } => {
                 tray::SystemTrayEvent::LeftClick {
                   position: *position,
@@ -5,18 +5,22 @@
 pub use crate::{
   runtime::{
     menu::{
-      MenuHash, MenuId, MenuIdRef, MenuUpdate, SystemTrayMenu, SystemTrayMenuEntry, TrayHandle,
+      CustomMenuItem, MenuHash, MenuId, MenuIdRef, MenuUpdate, SystemTrayMenu, SystemTrayMenuEntry,
+      TrayHandle,
     },
     window::dpi::{PhysicalPosition, PhysicalSize},
-    SystemTray, TrayIcon,
+    SystemTray, SystemTrayEvent as RuntimeSystemTrayEvent, TrayIcon,
   },
-  Runtime,
+  AppHandle, Runtime,
 };
 
+use serde::{Deserialize, Serialize};
 use tauri_macros::default_runtime;
 
 use std::{
   collections::HashMap,
+  io::{Error as IoError, ErrorKind},
+  path::PathBuf,
   sync::{Arc, Mutex},
 };
 
@@ -32,6 +36,731 @@ pub(crate) fn get_menu_ids(map: &mut HashMap<MenuHash, MenuId>, menu: &SystemTra
   }
 }
 
+#[derive(Debug)]
+enum TrayIconSource {
+  Path(PathBuf),
+  Bytes(Vec<u8>),
+  Svg(PathBuf),
+}
+
+/// An image format recognized from the file signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IconFormat {
+  Png,
+  Ico,
+  Svg,
+}
+
+/// The image formats the native tray accepts.
+#[cfg(windows)]
+const TRAY_ICON_FORMATS: &[IconFormat] = &[IconFormat::Ico];
+/// The image formats the native tray accepts.
+#[cfg(target_os = "macos")]
+const TRAY_ICON_FORMATS: &[IconFormat] = &[IconFormat::Png];
+/// The image formats the native tray accepts.
+#[cfg(not(any(windows, target_os = "macos")))]
+const TRAY_ICON_FORMATS: &[IconFormat] = &[IconFormat::Png, IconFormat::Ico, IconFormat::Svg];
+
+/// A description of [`TRAY_ICON_FORMATS`] for error messages.
+#[cfg(windows)]
+const TRAY_ICON_EXPECTED: &str = "an ICO image";
+/// A description of [`TRAY_ICON_FORMATS`] for error messages.
+#[cfg(target_os = "macos")]
+const TRAY_ICON_EXPECTED: &str = "a PNG image";
+/// A description of [`TRAY_ICON_FORMATS`] for error messages.
+#[cfg(not(any(windows, target_os = "macos")))]
+const TRAY_ICON_EXPECTED: &str = "a PNG, ICO or SVG image";
+
+impl IconFormat {
+  fn sniff(bytes: &[u8]) -> Option<Self> {
+    if bytes.starts_with(&[0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a]) {
+      Some(Self::Png)
+    } else if bytes.starts_with(&[0, 0, 1, 0]) {
+      Some(Self::Ico)
+    } else if String::from_utf8_lossy(&bytes[..bytes.len().min(1024)]).contains("<svg") {
+      Some(Self::Svg)
+    } else {
+      None
+    }
+  }
+
+  fn description(self) -> &'static str {
+    match self {
+      Self::Png => "a PNG image",
+      Self::Ico => "an ICO image",
+      Self::Svg => "an SVG image",
+    }
+  }
+
+  #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+  fn extension(self) -> &'static str {
+    match self {
+      Self::Png => "png",
+      Self::Ico => "ico",
+      Self::Svg => "svg",
+    }
+  }
+
+  /// Recognizes the image format, failing if the native tray does not accept it.
+  fn check(bytes: &[u8]) -> crate::Result<Self> {
+    match Self::sniff(bytes) {
+      Some(format) if TRAY_ICON_FORMATS.contains(&format) => Ok(format),
+      format => Err(crate::Error::InvalidTrayIcon {
+        expected: TRAY_ICON_EXPECTED,
+        got: format
+          .map(Self::description)
+          .unwrap_or("an unknown image format"),
+      }),
+    }
+  }
+}
+
+/// A builder for [`TrayIcon`]s that produces the variant required by the current platform:
+/// a [`TrayIcon::File`] on Linux and a [`TrayIcon::Raw`] on Windows and macOS.
+///
+/// The image must be an ICO on Windows, a PNG on macOS and a PNG, ICO or SVG on Linux,
+/// otherwise [`Self::build`] returns [`crate::Error::InvalidTrayIcon`].
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use tauri::TrayIconBuilder;
+/// tauri::Builder::default()
+///   .setup(|app| {
+///     #[cfg(windows)]
+///     let path = "icons/tray.ico";
+///     #[cfg(not(windows))]
+///     let path = "icons/tray.png";
+///     let icon = TrayIconBuilder::from_path(path).build()?;
+///     app.tray_handle().set_icon(icon)?;
+///     Ok(())
+///   });
+/// ```
+#[derive(Debug)]
+pub struct TrayIconBuilder {
+  source: TrayIconSource,
+  #[cfg_attr(target_os = "linux", allow(dead_code))]
+  size: Option<u32>,
+}
+
+impl TrayIconBuilder {
+  /// Creates a tray icon from an image file.
+  pub fn from_path<P: Into<PathBuf>>(path: P) -> Self {
+    Self {
+      source: TrayIconSource::Path(path.into()),
+      size: None,
+    }
+  }
+
+  /// Creates a tray icon from the bytes of an image file.
+  ///
+  /// On Linux the bytes are written to a file in the temporary directory, removed when the app exits.
+  pub fn from_bytes(bytes: impl Into<Vec<u8>>) -> Self {
+    Self {
+      source: TrayIconSource::Bytes(bytes.into()),
+      size: None,
+    }
+  }
+
+  /// Creates a tray icon from a SVG file.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / macOS:** Unsupported, [`Self::build`] returns an error.
+  pub fn from_svg<P: Into<PathBuf>>(path: P) -> Self {
+    Self {
+      source: TrayIconSource::Svg(path.into()),
+      size: None,
+    }
+  }
+
+  /// Sets the preferred icon size, in pixels.
+  ///
+  /// When the image is an `.ico` file with multiple entries, the entry closest to this size is used.
+  /// Requires the `icon-ico` feature and is ignored on Linux.
+  #[must_use]
+  pub fn with_size(mut self, size: u32) -> Self {
+    self.size.replace(size);
+    self
+  }
+
+  /// Builds the tray icon.
+  pub fn build(self) -> crate::Result<TrayIcon> {
+    match self.source {
+      TrayIconSource::Path(path) => {
+        let bytes = read_icon(&path)?;
+        IconFormat::check(&bytes)?;
+        #[cfg(target_os = "linux")]
+        return Ok(TrayIcon::File(path));
+        #[cfg(not(target_os = "linux"))]
+        return raw_tray_icon(bytes, self.size);
+      }
+      TrayIconSource::Bytes(bytes) => {
+        if bytes.is_empty() {
+          return Err(crate::Error::InvalidIcon(IoError::new(
+            ErrorKind::InvalidData,
+            "tray icon bytes are empty",
+          )));
+        }
+        #[allow(unused_variables)]
+        let format = IconFormat::check(&bytes)?;
+        #[cfg(target_os = "linux")]
+        return tray_icon_file(&bytes, format);
+        #[cfg(not(target_os = "linux"))]
+        return raw_tray_icon(bytes, self.size);
+      }
+      TrayIconSource::Svg(path) => {
+        let bytes = read_icon(&path)?;
+        if IconFormat::sniff(&bytes) != Some(IconFormat::Svg) {
+          return Err(crate::Error::InvalidTrayIcon {
+            expected: IconFormat::Svg.description(),
+            got: IconFormat::sniff(&bytes)
+              .map(IconFormat::description)
+              .unwrap_or("an unknown image format"),
+          });
+        }
+        #[cfg(target_os = "linux")]
+        return Ok(TrayIcon::File(path));
+        #[cfg(not(target_os = "linux"))]
+        return Err(crate::Error::InvalidIcon(IoError::new(
+          ErrorKind::InvalidInput,
+          "SVG tray icons are only supported on Linux",
+        )));
+      }
+    }
+  }
+}
+
+fn read_icon(path: &std::path::Path) -> crate::Result<Vec<u8>> {
+  if !path.is_file() {
+    return Err(crate::Error::InvalidIcon(IoError::new(
+      ErrorKind::NotFound,
+      format!("tray icon `{}` not found", path.display()),
+    )));
+  }
+  std::fs::read(path).map_err(crate::Error::InvalidIcon)
+}
+
+/// The icon files written by [`TrayIconBuilder`], removed when the app exits.
+#[cfg(target_os = "linux")]
+fn icon_files() -> &'static Mutex<Vec<PathBuf>> {
+  use once_cell::sync::Lazy;
+  static ICON_FILES: Lazy<Mutex<Vec<PathBuf>>> = Lazy::new(Default::default);
+  &ICON_FILES
+}
+
+/// Writes the icon to the temporary directory, since the Linux tray only accepts file paths.
+#[cfg(target_os = "linux")]
+fn tray_icon_file(bytes: &[u8], format: IconFormat) -> crate::Result<TrayIcon> {
+  use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+  };
+
+  let mut hasher = DefaultHasher::new();
+  bytes.hash(&mut hasher);
+  let path = std::env::temp_dir().join(format!(
+    "tauri-tray-icon-{}-{:x}.{}",
+    std::process::id(),
+    hasher.finish(),
+    format.extension()
+  ));
+  std::fs::write(&path, bytes).map_err(crate::Error::InvalidIcon)?;
+  let mut files = icon_files().lock().unwrap();
+  if !files.contains(&path) {
+    files.push(path.clone());
+  }
+  Ok(TrayIcon::File(path))
+}
+
+/// Removes the icon files written by [`TrayIconBuilder`].
+#[cfg(target_os = "linux")]
+pub(crate) fn remove_icon_files() {
+  for path in icon_files().lock().unwrap().drain(..) {
+    let _ = std::fs::remove_file(path);
+  }
+}
+
+#[cfg(not(target_os = "linux"))]
+#[allow(unused_variables)]
+fn raw_tray_icon(bytes: Vec<u8>, size: Option<u32>) -> crate::Result<TrayIcon> {
+  #[cfg(feature = "icon-ico")]
+  if let Some(size) = size {
+    if let Ok(icon_dir) = ico::IconDir::read(std::io::Cursor::new(&bytes)) {
+      if let Some(entry) = icon_dir
+        .entries()
+        .iter()
+        .min_by_key(|entry| (i64::from(entry.width()) - i64::from(size)).abs())
+      {
+        let mut resized = ico::IconDir::new(ico::ResourceType::Icon);
+        resized.add_entry(entry.clone());
+        let mut buffer = Vec::new();
+        resized
+          .write(&mut buffer)
+          .map_err(crate::Error::InvalidIcon)?;
+        return Ok(TrayIcon::Raw(buffer));
+      }
+    }
+  }
+  Ok(TrayIcon::Raw(bytes))
+}
+
+/// The tray state last requested through a [`SystemTrayHandle`].
+#[derive(Debug, Default)]
+pub(crate) struct TrayState {
+  pub(crate) icon: Option<TrayIcon>,
+  pub(crate) menu: Option<SystemTrayMenu>,
+  /// The title and enabled state of the items in loading state, restored when loading finishes.
+  loading: HashMap<MenuHash, (String, bool)>,
+  /// The content hash of the icon currently set on the native tray.
+  icon_hash: Option<u64>,
+  /// The menu item that quits the app and its optional confirmation message.
+  quit_item: Option<(MenuId, Option<String>)>,
+  /// Whether the native tray was removed.
+  destroyed: bool,
+  /// The tray icon position and size reported by the last click event.
+  geometry: Option<(PhysicalPosition<f64>, PhysicalSize<f64>)>,
+}
+
+/// What to do when a menu item is clicked.
+#[derive(Debug, PartialEq)]
+enum QuitAction {
+  Exit,
+  Confirm(String),
+}
+
+/// Updates the tray state from a runtime tray event, before the event reaches the app listeners.
+pub(crate) fn on_tray_event<R: Runtime>(
+  app: &AppHandle<R>,
+  ids: &Mutex<HashMap<MenuHash, MenuId>>,
+  state: &Mutex<TrayState>,
+  event: &RuntimeSystemTrayEvent,
+) {
+  match event {
+    RuntimeSystemTrayEvent::MenuItemClick(id) => {
+      let id = ids.lock().unwrap().get(id).cloned();
+      if let Some(id) = id {
+        on_menu_item_click(app, state, &id);
+      }
+    }
+    RuntimeSystemTrayEvent::LeftClick { position, size }
+    | RuntimeSystemTrayEvent::RightClick { position, size }
+    | RuntimeSystemTrayEvent::DoubleClick { position, size } => {
+      state.lock().unwrap().geometry.replace((*position, *size));
+    }
+  }
+}
+
+/// Quits the app if the clicked menu item was registered with [`SystemTrayHandle::set_quit_item`].
+fn on_menu_item_click<R: Runtime>(app: &AppHandle<R>, state: &Mutex<TrayState>, id: &str) {
+  let exit_app = app.clone();
+  #[cfg(feature = "dialog")]
+  let dialog_app = app.clone();
+  #[allow(unused_variables)]
+  let confirm = move |message: String, on_answer: Box<dyn FnOnce(bool) + Send>| {
+    #[cfg(feature = "dialog")]
+    crate::api::dialog::confirm(
+      None::<&crate::Window<R>>,
+      dialog_app.package_info().name.clone(),
+      message,
+      on_answer,
+    );
+  };
+  on_quit_click(state, id, move || exit_app.exit(0), confirm);
+}
+
+/// Runs the quit action of the clicked menu item: calls `exit`, or asks with `confirm` first
+/// and calls `exit` if the user accepts.
+fn on_quit_click<E, C>(state: &Mutex<TrayState>, id: &str, exit: E, confirm: C)
+where
+  E: FnOnce() + Send + 'static,
+  C: FnOnce(String, Box<dyn FnOnce(bool) + Send>),
+{
+  let action = state.lock().unwrap().quit_action(id);
+  match action {
+    Some(QuitAction::Exit) => exit(),
+    Some(QuitAction::Confirm(message)) => confirm(
+      message,
+      Box::new(move |confirmed| {
+        if confirmed {
+          exit();
+        }
+      }),
+    ),
+    None => {}
+  }
+}
+
+/// Checks that the icon is the [`TrayIcon`] variant the platform accepts.
+fn validate_tray_icon(icon: &TrayIcon) -> crate::Result<()> {
+  #[allow(unreachable_patterns)]
+  let got = match icon {
+    TrayIcon::File(_) => "a `TrayIcon::File`",
+    TrayIcon::Raw(_) => "a `TrayIcon::Raw`",
+    _ => return Ok(()),
+  };
+  let expected = if cfg!(target_os = "linux") {
+    "a `TrayIcon::File`"
+  } else {
+    "a `TrayIcon::Raw`"
+  };
+  if got == expected {
+    Ok(())
+  } else {
+    Err(crate::Error::InvalidTrayIcon { expected, got })
+  }
+}
+
+/// Hashes the icon content, so setting the same image again can be skipped.
+fn icon_hash(icon: &TrayIcon) -> Option<u64> {
+  use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+  };
+
+  let mut hasher = DefaultHasher::new();
+  #[allow(unreachable_patterns)]
+  match icon {
+    TrayIcon::File(path) => match std::fs::read(path) {
+      Ok(bytes) => bytes.hash(&mut hasher),
+      Err(_) => return None,
+    },
+    TrayIcon::Raw(bytes) => bytes.hash(&mut hasher),
+    _ => return None,
+  }
+  Some(hasher.finish())
+}
+
+impl TrayState {
+  /// Fails if the native tray was removed.
+  fn ensure_alive(&self) -> crate::Result<()> {
+    if self.destroyed {
+      Err(crate::Error::SystemTrayDestroyed)
+    } else {
+      Ok(())
+    }
+  }
+
+  fn quit_action(&self, id: &str) -> Option<QuitAction> {
+    match &self.quit_item {
+      Some((quit_id, confirm)) if quit_id == id => Some(match confirm {
+        Some(message) => QuitAction::Confirm(message.clone()),
+        None => QuitAction::Exit,
+      }),
+      _ => None,
+    }
+  }
+
+  /// Finds a menu item on the cached menu.
+  fn item(&self, id: MenuHash) -> Option<&CustomMenuItem> {
+    self.menu.as_ref().and_then(|menu| find_menu_item(menu, id))
+  }
+
+  /// Applies a menu item update to the cached menu.
+  fn update_item(&mut self, id: MenuHash, update: &MenuUpdate) {
+    if let Some(menu) = &mut self.menu {
+      update_menu_item(menu, id, update);
+    }
+  }
+}
+
+fn find_menu_item(menu: &SystemTrayMenu, id: MenuHash) -> Option<&CustomMenuItem> {
+  for item in &menu.items {
+    match item {
+      SystemTrayMenuEntry::CustomItem(c) if c.id == id => return Some(c),
+      SystemTrayMenuEntry::Submenu(s) => {
+        if let Some(c) = find_menu_item(&s.inner, id) {
+          return Some(c);
+        }
+      }
+      _ => {}
+    }
+  }
+  None
+}
+
+fn insert_menu_entry(menu: &mut SystemTrayMenu, entry: SystemTrayMenuEntry, position: usize) {
+  let position = position.min(menu.items.len());
+  menu.items.insert(position, entry);
+}
+
+fn find_submenu<'a>(menu: &'a mut SystemTrayMenu, title: &str) -> Option<&'a mut SystemTrayMenu> {
+  for item in &mut menu.items {
+    if let SystemTrayMenuEntry::Submenu(s) = item {
+      if s.title == title {
+        return Some(&mut s.inner);
+      }
+      if let Some(submenu) = find_submenu(&mut s.inner, title) {
+        return Some(submenu);
+      }
+    }
+  }
+  None
+}
+
+fn remove_menu_item(menu: &mut SystemTrayMenu, id: MenuIdRef<'_>) -> bool {
+  let len = menu.items.len();
+  menu
+    .items
+    .retain(|item| !matches!(item, SystemTrayMenuEntry::CustomItem(c) if c.id_str == id));
+  if menu.items.len() != len {
+    return true;
+  }
+  for item in &mut menu.items {
+    if let SystemTrayMenuEntry::Submenu(s) = item {
+      if remove_menu_item(&mut s.inner, id) {
+        return true;
+      }
+    }
+  }
+  false
+}
+
+fn update_menu_item(menu: &mut SystemTrayMenu, id: MenuHash, update: &MenuUpdate) -> bool {
+  for item in &mut menu.items {
+    match item {
+      SystemTrayMenuEntry::CustomItem(c) if c.id == id => {
+        match update {
+          MenuUpdate::SetEnabled(enabled) => c.enabled = *enabled,
+          MenuUpdate::SetTitle(title) => c.title = title.clone(),
+          MenuUpdate::SetSelected(selected) => c.selected = *selected,
+          #[cfg(target_os = "macos")]
+          MenuUpdate::SetNativeImage(image) => c.native_image = Some(*image),
+          #[allow(unreachable_patterns)]
+          _ => {}
+        }
+        return true;
+      }
+      SystemTrayMenuEntry::Submenu(s) => {
+        if update_menu_item(&mut s.inner, id, update) {
+          return true;
+        }
+      }
+      _ => {}
+    }
+  }
+  false
+}
+
+/// Collects the state of the custom items of the menu and its submenus, in menu order.
+fn snapshot_items(
+  menu: &SystemTrayMenu,
+  ids: &HashMap<MenuHash, MenuId>,
+  items: &mut Vec<TrayItemSnapshot>,
+) {
+  for item in &menu.items {
+    match item {
+      SystemTrayMenuEntry::CustomItem(c) => items.push(TrayItemSnapshot {
+        id: ids.get(&c.id).cloned().unwrap_or_else(|| c.id_str.clone()),
+        title: c.title.clone(),
+        enabled: c.enabled,
+        selected: c.selected,
+      }),
+      SystemTrayMenuEntry::Submenu(s) => snapshot_items(&s.inner, ids, items),
+      _ => {}
+    }
+  }
+}
+
+/// Applies a menu item update on the native tray.
+#[allow(unused_variables)]
+fn native_update_item<H: TrayHandle>(
+  handler: &H,
+  id: MenuHash,
+  id_str: MenuIdRef<'_>,
+  update: MenuUpdate,
+) -> crate::Result<()> {
+  #[cfg(feature = "tracing")]
+  let operation = format!("{:?}", update);
+  let result: crate::Result<()> = handler.update_item(id, update).map_err(Into::into);
+  #[cfg(feature = "tracing")]
+  tracing::debug!(
+    operation = %operation,
+    item = %id_str,
+    result = ?result,
+    "system tray menu item update"
+  );
+  result
+}
+
+/// A serializable capture of the tray icon and menu item states.
+///
+/// Created with [`SystemTrayHandle::snapshot`] and applied with [`SystemTrayHandle::restore`].
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraySnapshot {
+  /// The icon path, when the icon is a [`TrayIcon::File`].
+  pub icon_path: Option<PathBuf>,
+  /// The icon content, when the icon is a [`TrayIcon::Raw`].
+  pub icon_bytes: Option<Vec<u8>>,
+  /// The state of each custom menu item, including the submenu items.
+  pub items: Vec<TrayItemSnapshot>,
+}
+
+/// The state of a tray menu item in a [`TraySnapshot`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrayItemSnapshot {
+  /// The menu item id.
+  pub id: MenuId,
+  /// The menu item title.
+  pub title: String,
+  /// Whether the menu item is enabled.
+  pub enabled: bool,
+  /// Whether the menu item is selected.
+  pub selected: bool,
+}
+
+/// A status displayed on the system tray through a [`TrayStatusController`].
+pub trait TrayStatus: PartialEq {
+  /// The tray icon of this status.
+  fn icon(&self) -> TrayIcon;
+
+  /// The state of the menu items that change with this status.
+  ///
+  /// Items that are not listed keep their current state.
+  fn items(&self) -> Vec<TrayItemSnapshot> {
+    Vec::new()
+  }
+}
+
+/// Applies the icon and menu item states of a [`TrayStatus`] to the system tray.
+///
+/// Only the values that differ from the current tray state are updated.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use tauri::{TrayIcon, TrayItemSnapshot, TrayStatus, TrayStatusController};
+///
+/// #[derive(PartialEq)]
+/// enum Connection {
+///   Connected,
+///   Offline,
+/// }
+///
+/// impl TrayStatus for Connection {
+///   fn icon(&self) -> TrayIcon {
+///     match self {
+///       Self::Connected => TrayIcon::File("icons/connected.png".into()),
+///       Self::Offline => TrayIcon::File("icons/offline.png".into()),
+///     }
+///   }
+///
+///   fn items(&self) -> Vec<TrayItemSnapshot> {
+///     vec![TrayItemSnapshot {
+///       id: "connect".into(),
+///       title: if *self == Self::Connected { "Disconnect" } else { "Connect" }.into(),
+///       enabled: true,
+///       selected: false,
+///     }]
+///   }
+/// }
+///
+/// tauri::Builder::default()
+///   .setup(|app| {
+///     let mut status = TrayStatusController::new(app.tray_handle());
+///     status.set_state(Connection::Offline)?;
+///     Ok(())
+///   });
+/// ```
+#[derive(Debug)]
+pub struct TrayStatusController<R: Runtime, S: TrayStatus> {
+  tray: SystemTrayHandle<R>,
+  state: Option<S>,
+}
+
+impl<R: Runtime, S: TrayStatus> TrayStatusController<R, S> {
+  /// Creates a controller for the given tray, with no status applied yet.
+  pub fn new(tray: SystemTrayHandle<R>) -> Self {
+    Self { tray, state: None }
+  }
+
+  /// The status that was last applied.
+  pub fn state(&self) -> Option<&S> {
+    self.state.as_ref()
+  }
+
+  /// Applies the given status. Setting the current status again is a no-op.
+  pub fn set_state(&mut self, state: S) -> crate::Result<()> {
+    if self.state.as_ref() == Some(&state) {
+      return Ok(());
+    }
+    let mut snapshot = self.tray.snapshot();
+    #[allow(unreachable_patterns)]
+    match state.icon() {
+      TrayIcon::File(path) => {
+        snapshot.icon_path = Some(path);
+        snapshot.icon_bytes = None;
+      }
+      TrayIcon::Raw(bytes) => {
+        snapshot.icon_path = None;
+        snapshot.icon_bytes = Some(bytes);
+      }
+      _ => {}
+    }
+    for item in state.items() {
+      if let Some(current) = snapshot.items.iter_mut().find(|c| c.id == item.id) {
+        *current = item;
+      }
+    }
+    self.tray.restore(snapshot)?;
+    self.state.replace(state);
+    Ok(())
+  }
+}
+
+/// A "recent items" list on a submenu of the tray menu, keeping the most recent `max` items first.
+///
+/// Created with [`SystemTrayHandle::recent_items`].
+/// The recent items are the custom items of the submenu; separators and nested submenus are kept as they are.
+#[derive(Debug)]
+pub struct RecentItems<R: Runtime> {
+  tray: SystemTrayHandle<R>,
+  submenu: String,
+  max: usize,
+}
+
+impl<R: Runtime> RecentItems<R> {
+  /// Adds an item at the top of the submenu, removing the oldest items beyond the maximum.
+  ///
+  /// Pushing the id of an item already on the submenu moves it to the top with the new title.
+  /// Fails with [`crate::Error::MenuItemNotFound`] if the menu has no submenu with the title of the list.
+  /// The runtime can't modify the native menu structure, so the whole menu is set again.
+  pub fn push(&self, id: impl Into<MenuId>, title: impl Into<String>) -> crate::Result<()> {
+    let id = id.into();
+    let mut state = self.tray.state.lock().unwrap();
+    let mut menu = state.menu.clone().unwrap_or_default();
+    let submenu = find_submenu(&mut menu, &self.submenu)
+      .ok_or_else(|| crate::Error::MenuItemNotFound(self.submenu.clone()))?;
+    submenu
+      .items
+      .retain(|item| !matches!(item, SystemTrayMenuEntry::CustomItem(c) if c.id_str == id));
+    submenu.items.insert(
+      0,
+      SystemTrayMenuEntry::CustomItem(CustomMenuItem::new(id, title)),
+    );
+    let mut kept = 0;
+    submenu.items.retain(|item| match item {
+      SystemTrayMenuEntry::CustomItem(_) => {
+        kept += 1;
+        kept <= self.max
+      }
+      _ => true,
+    });
+    self.tray.set_menu_locked(&mut state, menu)
+  }
+}
+
+/// Formats a progress label like `Download — 42%`, clamping `fraction` to `0.0..=1.0`.
+fn format_progress_title(label: &str, fraction: f64) -> String {
+  let fraction = if fraction.is_nan() {
+    0.0
+  } else {
+    fraction.clamp(0.0, 1.0)
+  };
+  format!("{} — {}%", label, (fraction * 100.0).round() as u8)
+}
+
 /// System tray event.
 #[cfg_attr(doc_cfg, doc(cfg(feature = "system-tray")))]
 #[non_exhaustive]
@@ -87,14 +816,20 @@ pub enum SystemTrayEvent {
 #[derive(Debug)]
 pub struct SystemTrayHandle<R: Runtime> {
   pub(crate) ids: Arc<Mutex<HashMap<MenuHash, MenuId>>>,
+  pub(crate) state: Arc<Mutex<TrayState>>,
   pub(crate) inner: R::TrayHandler,
+  #[cfg(windows)]
+  pub(crate) runtime_handle: R::Handle,
 }
 
 impl<R: Runtime> Clone for SystemTrayHandle<R> {
   fn clone(&self) -> Self {
     Self {
       ids: self.ids.clone(),
+      state: self.state.clone(),
       inner: self.inner.clone(),
+      #[cfg(windows)]
+      runtime_handle: self.runtime_handle.clone(),
     }
   }
 }
@@ -104,6 +839,8 @@ impl<R: Runtime> Clone for SystemTrayHandle<R> {
 #[derive(Debug)]
 pub struct SystemTrayMenuItemHandle<R: Runtime> {
   id: MenuHash,
+  id_str: MenuId,
+  state: Arc<Mutex<TrayState>>,
   tray_handler: R::TrayHandler,
 }
 
@@ -111,6 +848,8 @@ impl<R: Runtime> Clone for SystemTrayMenuItemHandle<R> {
   fn clone(&self) -> Self {
     Self {
       id: self.id,
+      id_str: self.id_str.clone(),
+      state: self.state.clone(),
       tray_handler: self.tray_handler.clone(),
     }
   }
@@ -118,32 +857,407 @@ impl<R: Runtime> Clone for SystemTrayMenuItemHandle<R> {
 
 impl<R: Runtime> SystemTrayHandle<R> {
   /// Gets a handle to the menu item that has the specified `id`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the menu has no item with this `id`. See [`Self::try_get_item`].
   pub fn get_item(&self, id: MenuIdRef<'_>) -> SystemTrayMenuItemHandle<R> {
-    for (raw, item_id) in self.ids.lock().unwrap().iter() {
-      if item_id == id {
-        return SystemTrayMenuItemHandle {
-          id: *raw,
-          tray_handler: self.inner.clone(),
-        };
-      }
-    }
-    panic!("item id not found")
+    self.try_get_item(id).expect("item id not found")
   }
 
-  /// Updates the tray icon. Must be a [`TrayIcon::File`] on Linux and a [`TrayIcon::Raw`] on Windows and macOS.
+  /// Gets a handle to the menu item that has the specified `id`, or `None` if the menu has no such item.
+  pub fn try_get_item(&self, id: MenuIdRef<'_>) -> Option<SystemTrayMenuItemHandle<R>> {
+    self
+      .ids
+      .lock()
+      .unwrap()
+      .iter()
+      .find(|(_, item_id)| *item_id == id)
+      .map(|(raw, item_id)| SystemTrayMenuItemHandle {
+        id: *raw,
+        id_str: item_id.clone(),
+        state: self.state.clone(),
+        tray_handler: self.inner.clone(),
+      })
+  }
+
+  /// The ids of all the menu items, in no particular order.
+  pub fn items(&self) -> Vec<MenuId> {
+    self.ids.lock().unwrap().values().cloned().collect()
+  }
+
+  /// Updates the tray icon. Must be a [`TrayIcon::File`] on Linux and a [`TrayIcon::Raw`] on Windows and macOS,
+  /// otherwise [`crate::Error::InvalidTrayIcon`] is returned. Use [`TrayIconBuilder`] to get the right variant.
+  ///
+  /// Setting the same image that is currently displayed is a no-op.
   pub fn set_icon(&self, icon: TrayIcon) -> crate::Result<()> {
-    self.inner.set_icon(icon).map_err(Into::into)
+    validate_tray_icon(&icon)?;
+    let hash = icon_hash(&icon);
+    let mut state = self.state.lock().unwrap();
+    state.ensure_alive()?;
+    if hash.is_some() && state.icon_hash == hash {
+      return Ok(());
+    }
+    self.native_set_icon(icon.clone())?;
+    state.icon.replace(icon);
+    state.icon_hash = hash;
+    Ok(())
+  }
+
+  /// Forgets the currently displayed icon, so the next [`Self::set_icon`] call always updates the native tray.
+  pub fn clear_icon_cache(&self) {
+    self.state.lock().unwrap().icon_hash = None;
   }
 
   /// Updates the tray menu.
   pub fn set_menu(&self, menu: SystemTrayMenu) -> crate::Result<()> {
+    self.set_menu_locked(&mut self.state.lock().unwrap(), menu)
+  }
+
+  /// Updates the tray menu, with the state lock held by the caller so it can modify the cached menu.
+  fn set_menu_locked(&self, state: &mut TrayState, menu: SystemTrayMenu) -> crate::Result<()> {
+    state.ensure_alive()?;
+    self.native_set_menu(menu.clone())?;
     let mut ids = HashMap::new();
     get_menu_ids(&mut ids, &menu);
-    self.inner.set_menu(menu)?;
+    state.menu.replace(menu);
     *self.ids.lock().unwrap() = ids;
     Ok(())
   }
 
+  /// Applies updates to several menu items, in order, while holding the tray state lock.
+  ///
+  /// Every update is still a separate native item update, the same as calling the item setters
+  /// one by one, so this does not reduce flicker; it only checks all the ids up front and keeps
+  /// other tray changes from interleaving with the batch.
+  ///
+  /// Fails without applying any update if one of the ids is not on the menu.
+  /// Otherwise stops at the first failed native update, keeping the updates applied before it.
+  pub fn update_batch(&self, updates: Vec<(MenuId, MenuUpdate)>) -> crate::Result<()> {
+    let mut state = self.state.lock().unwrap();
+    state.ensure_alive()?;
+    let updates = {
+      let ids = self.ids.lock().unwrap();
+      updates
+        .into_iter()
+        .map(|(id, update)| {
+          ids
+            .iter()
+            .find(|(_, item_id)| **item_id == id)
+            .map(|(raw, _)| (*raw, id.clone(), update))
+            .ok_or(crate::Error::MenuItemNotFound(id))
+        })
+        .collect::<crate::Result<Vec<_>>>()?
+    };
+    for (id, id_str, update) in updates {
+      native_update_item(&self.inner, id, &id_str, update.clone())?;
+      state.update_item(id, &update);
+    }
+    Ok(())
+  }
+
+  /// Inserts an entry on the root level of the tray menu, at `position` or at the end if it is out of bounds.
+  ///
+  /// The runtime can't modify the native menu structure, so the whole menu is set again.
+  pub fn insert_item(&self, entry: SystemTrayMenuEntry, position: usize) -> crate::Result<()> {
+    let mut state = self.state.lock().unwrap();
+    let mut menu = state.menu.clone().unwrap_or_default();
+    insert_menu_entry(&mut menu, entry, position);
+    self.set_menu_locked(&mut state, menu)
+  }
+
+  /// Manages the submenu with the given `title` as a list of the `max` most recent items, e.g. recent files.
+  ///
+  /// Submenus have no id, so the submenu is found by title, searching nested submenus too.
+  pub fn recent_items(&self, submenu: impl Into<String>, max: usize) -> RecentItems<R> {
+    RecentItems {
+      tray: self.clone(),
+      submenu: submenu.into(),
+      max,
+    }
+  }
+
+  /// Inserts an entry on the first submenu with the given `title`, at `position` or at the end if it is out of bounds.
+  ///
+  /// Nested submenus are searched too. Submenus have no id, so they are found by title.
+  /// Fails with [`crate::Error::MenuItemNotFound`] if the menu has no such submenu.
+  /// The runtime can't modify the native menu structure, so the whole menu is set again.
+  pub fn insert_submenu_item(
+    &self,
+    submenu: &str,
+    entry: SystemTrayMenuEntry,
+    position: usize,
+  ) -> crate::Result<()> {
+    let mut state = self.state.lock().unwrap();
+    let mut menu = state.menu.clone().unwrap_or_default();
+    let parent = find_submenu(&mut menu, submenu)
+      .ok_or_else(|| crate::Error::MenuItemNotFound(submenu.into()))?;
+    insert_menu_entry(parent, entry, position);
+    self.set_menu_locked(&mut state, menu)
+  }
+
+  /// Removes the custom item with the given `id` from the tray menu or its submenus.
+  ///
+  /// Does nothing if the menu has no such item.
+  /// The runtime can't modify the native menu structure, so the whole menu is set again.
+  pub fn remove_item(&self, id: MenuIdRef<'_>) -> crate::Result<()> {
+    let mut state = self.state.lock().unwrap();
+    let mut menu = match state.menu.clone() {
+      Some(menu) => menu,
+      None => return Ok(()),
+    };
+    if remove_menu_item(&mut menu, id) {
+      self.set_menu_locked(&mut state, menu)
+    } else {
+      Ok(())
+    }
+  }
+
+  /// The tray icon position and size, as reported by the last click on the icon.
+  ///
+  /// The runtime can't query the geometry, so this is `None` until the icon is clicked
+  /// and may be stale if the taskbar moved since then.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** Unsupported, always `None`.
+  pub fn geometry(&self) -> crate::Result<Option<(PhysicalPosition<f64>, PhysicalSize<f64>)>> {
+    let state = self.state.lock().unwrap();
+    state.ensure_alive()?;
+    Ok(state.geometry)
+  }
+
+  /// Makes the menu item with the given `id` quit the app when clicked.
+  ///
+  /// If `confirm` is set, a confirmation dialog with that message is displayed first,
+  /// which requires the `dialog` feature.
+  /// Fails with [`crate::Error::MenuItemNotFound`] if the menu has no item with this `id`.
+  pub fn set_quit_item(&self, id: MenuId, confirm: Option<String>) -> crate::Result<()> {
+    if !self
+      .ids
+      .lock()
+      .unwrap()
+      .values()
+      .any(|item_id| *item_id == id)
+    {
+      return Err(crate::Error::MenuItemNotFound(id));
+    }
+    #[cfg(not(feature = "dialog"))]
+    if confirm.is_some() {
+      return Err(crate::Error::ApiNotEnabled(
+        "the `dialog` feature is required to confirm the tray quit item".into(),
+      ));
+    }
+    self.state.lock().unwrap().quit_item.replace((id, confirm));
+    Ok(())
+  }
+
+  /// Applies the current icon and menu to the native tray again.
+  ///
+  /// The tray icon can be silently removed by the OS, e.g. when the Windows Explorer restarts and the taskbar is recreated.
+  /// The runtime does not notify the `TaskbarCreated` message, so call this when you detect the tray is gone.
+  pub fn reassert(&self) -> crate::Result<()> {
+    let state = self.state.lock().unwrap();
+    state.ensure_alive()?;
+    if let Some(icon) = &state.icon {
+      self.native_set_icon(icon.clone())?;
+    }
+    if let Some(menu) = &state.menu {
+      self.native_set_menu(menu.clone())?;
+    }
+    Ok(())
+  }
+
+  /// Captures the current icon and the title, enabled and selected state of every menu item.
+  ///
+  /// The tooltip and the menu structure are not part of the snapshot.
+  pub fn snapshot(&self) -> TraySnapshot {
+    let state = self.state.lock().unwrap();
+    let mut snapshot = TraySnapshot::default();
+    #[allow(unreachable_patterns)]
+    match &state.icon {
+      Some(TrayIcon::File(path)) => snapshot.icon_path = Some(path.clone()),
+      Some(TrayIcon::Raw(bytes)) => snapshot.icon_bytes = Some(bytes.clone()),
+      _ => {}
+    }
+    if let Some(menu) = &state.menu {
+      snapshot_items(menu, &self.ids.lock().unwrap(), &mut snapshot.items);
+    }
+    snapshot
+  }
+
+  /// Applies a snapshot created with [`Self::snapshot`].
+  ///
+  /// Only the values that differ from the current state are updated.
+  /// Items that are no longer on the menu are ignored.
+  pub fn restore(&self, snapshot: TraySnapshot) -> crate::Result<()> {
+    let current = self.snapshot();
+    if snapshot.icon_path != current.icon_path || snapshot.icon_bytes != current.icon_bytes {
+      if let Some(path) = snapshot.icon_path {
+        self.set_icon(TrayIcon::File(path))?;
+      } else if let Some(bytes) = snapshot.icon_bytes {
+        self.set_icon(TrayIcon::Raw(bytes))?;
+      }
+    }
+    for item in snapshot.items {
+      let (current, handle) = match (
+        current.items.iter().find(|c| c.id == item.id),
+        self.try_get_item(&item.id),
+      ) {
+        (Some(current), Some(handle)) => (current, handle),
+        _ => continue,
+      };
+      if current.title != item.title {
+        handle.set_title(item.title)?;
+      }
+      if current.enabled != item.enabled {
+        handle.set_enabled(item.enabled)?;
+      }
+      if current.selected != item.selected {
+        handle.set_selected(item.selected)?;
+      }
+    }
+    Ok(())
+  }
+
+  /// Disables every enabled menu item while `f` runs, enabling them again once it completes.
+  ///
+  /// Only the items disabled here are enabled again, other changes made while `f` runs are kept.
+  /// The items are also enabled again if the future panics or is dropped before completion.
+  /// The icon is not changed, use [`Self::with_busy_spinner`] to animate it.
+  pub async fn with_busy<F, Fut, T>(&self, f: F) -> crate::Result<T>
+  where
+    F: FnOnce() -> Fut,
+    Fut: std::future::Future<Output = T>,
+  {
+    self
+      .with_busy_spinner(Vec::new(), std::time::Duration::ZERO, f)
+      .await
+  }
+
+  /// Like [`Self::with_busy`], also cycling the tray icon through the spinner `frames` every `interval` while `f` runs.
+  ///
+  /// The icon set before is restored once `f` completes, panics or is dropped.
+  /// Fails without running `f` if one of the frames is not valid on the platform, see [`Self::set_icon`].
+  pub async fn with_busy_spinner<F, Fut, T>(
+    &self,
+    frames: Vec<TrayIcon>,
+    interval: std::time::Duration,
+    f: F,
+  ) -> crate::Result<T>
+  where
+    F: FnOnce() -> Fut,
+    Fut: std::future::Future<Output = T>,
+  {
+    use futures::future::{self, Either};
+
+    struct BusyGuard<R: Runtime> {
+      tray: SystemTrayHandle<R>,
+      items: Vec<SystemTrayMenuItemHandle<R>>,
+      /// The icon to restore, if the spinner ran.
+      icon: Option<TrayIcon>,
+    }
+
+    impl<R: Runtime> BusyGuard<R> {
+      /// Enables the disabled items and restores the icon, returning the first error.
+      fn release(&mut self) -> crate::Result<()> {
+        let mut result = Ok(());
+        for item in self.items.drain(..) {
+          if let Err(e) = item.set_enabled(true) {
+            if result.is_ok() {
+              result = Err(e);
+            }
+          }
+        }
+        if let Some(icon) = self.icon.take() {
+          if let Err(e) = self.tray.set_icon(icon) {
+            if result.is_ok() {
+              result = Err(e);
+            }
+          }
+        }
+        result
+      }
+    }
+
+    impl<R: Runtime> Drop for BusyGuard<R> {
+      fn drop(&mut self) {
+        // unwrapping a poisoned lock while unwinding would abort the process
+        if !self.tray.state.is_poisoned() {
+          let _ = self.release();
+        }
+      }
+    }
+
+    for frame in &frames {
+      validate_tray_icon(frame)?;
+    }
+    let mut guard = BusyGuard {
+      tray: self.clone(),
+      items: Vec::new(),
+      icon: None,
+    };
+    for item in self
+      .snapshot()
+      .items
+      .into_iter()
+      .filter(|item| item.enabled)
+    {
+      if let Some(handle) = self.try_get_item(&item.id) {
+        handle.set_enabled(false)?;
+        guard.items.push(handle);
+      }
+    }
+    if !frames.is_empty() {
+      guard.icon = self.state.lock().unwrap().icon.clone();
+    }
+    let spinner = async {
+      // without frames the spinner never completes and `f` runs alone
+      for frame in frames.iter().cycle() {
+        let _ = self.set_icon(frame.clone());
+        tokio::time::sleep(interval).await;
+      }
+      future::pending::<()>().await
+    };
+    let output = match future::select(Box::pin(f()), Box::pin(spinner)).await {
+      Either::Left((output, _)) => output,
+      Either::Right(_) => unreachable!("the spinner never completes"),
+    };
+    guard.release()?;
+    Ok(output)
+  }
+
+  /// Removes the tray icon from the taskbar.
+  ///
+  /// The tray cannot be recreated afterwards. Later updates through this handle
+  /// or its menu item handles return [`crate::Error::SystemTrayDestroyed`].
+  #[cfg(windows)]
+  #[cfg_attr(doc_cfg, doc(cfg(windows)))]
+  pub fn destroy(&self) -> crate::Result<()> {
+    use crate::runtime::RuntimeHandle;
+
+    self.state.lock().unwrap().ensure_alive()?;
+    self.runtime_handle.remove_system_tray()?;
+    self.state.lock().unwrap().destroyed = true;
+    Ok(())
+  }
+
+  /// Sets the icon on the native tray.
+  fn native_set_icon(&self, icon: TrayIcon) -> crate::Result<()> {
+    let result: crate::Result<()> = self.inner.set_icon(icon).map_err(Into::into);
+    #[cfg(feature = "tracing")]
+    tracing::debug!(operation = "set_icon", result = ?result, "system tray update");
+    result
+  }
+
+  /// Sets the menu on the native tray.
+  fn native_set_menu(&self, menu: SystemTrayMenu) -> crate::Result<()> {
+    let result: crate::Result<()> = self.inner.set_menu(menu).map_err(Into::into);
+    #[cfg(feature = "tracing")]
+    tracing::debug!(operation = "set_menu", result = ?result, "system tray update");
+    result
+  }
+
   /// Support [macOS tray icon template](https://developer.apple.com/documentation/appkit/nsimage/1520017-template?language=objc) to adjust automatically based on taskbar color.
   #[cfg(target_os = "macos")]
   pub fn set_icon_as_template(&self, is_template: bool) -> crate::Result<()> {
@@ -155,36 +1269,759 @@ impl<R: Runtime> SystemTrayHandle<R> {
 }
 
 impl<R: Runtime> SystemTrayMenuItemHandle<R> {
+  fn update(&self, update: MenuUpdate) -> crate::Result<()> {
+    self.update_locked(&mut self.state.lock().unwrap(), update)
+  }
+
+  /// Updates the menu item, with the state lock held by the caller.
+  fn update_locked(&self, state: &mut TrayState, update: MenuUpdate) -> crate::Result<()> {
+    state.ensure_alive()?;
+    native_update_item(&self.tray_handler, self.id, &self.id_str, update.clone())?;
+    state.update_item(self.id, &update);
+    Ok(())
+  }
+
+  /// The menu item id, as in [`SystemTrayEvent::MenuItemClick`](crate::SystemTrayEvent::MenuItemClick).
+  pub fn id(&self) -> MenuId {
+    self.id_str.clone()
+  }
+
   /// Modifies the enabled state of the menu item.
   pub fn set_enabled(&self, enabled: bool) -> crate::Result<()> {
-    self
-      .tray_handler
-      .update_item(self.id, MenuUpdate::SetEnabled(enabled))
-      .map_err(Into::into)
+    self.update(MenuUpdate::SetEnabled(enabled))
   }
 
   /// Modifies the title (label) of the menu item.
   pub fn set_title<S: Into<String>>(&self, title: S) -> crate::Result<()> {
-    self
-      .tray_handler
-      .update_item(self.id, MenuUpdate::SetTitle(title.into()))
-      .map_err(Into::into)
+    self.update(MenuUpdate::SetTitle(title.into()))
+  }
+
+  /// Modifies the title of the menu item to display the progress of an operation, e.g. `Download — 42%`.
+  ///
+  /// The `fraction` is clamped to the `0.0..=1.0` range.
+  pub fn set_progress_title(&self, label: &str, fraction: f64) -> crate::Result<()> {
+    self.set_title(format_progress_title(label, fraction))
+  }
+
+  /// Toggles the loading state of the menu item.
+  ///
+  /// While loading, the item is disabled and its title is suffixed with `…`.
+  /// The previous title and enabled state are restored when `loading` is `false`.
+  /// Fails with [`crate::Error::MenuItemNotFound`] if the item is no longer on the menu.
+  pub fn set_loading(&self, loading: bool) -> crate::Result<()> {
+    let mut state = self.state.lock().unwrap();
+    if loading {
+      if state.loading.contains_key(&self.id) {
+        return Ok(());
+      }
+      let (title, enabled) = state
+        .item(self.id)
+        .map(|item| (item.title.clone(), item.enabled))
+        .ok_or_else(|| crate::Error::MenuItemNotFound(self.id_str.clone()))?;
+      self.update_locked(&mut state, MenuUpdate::SetEnabled(false))?;
+      state.loading.insert(self.id, (title.clone(), enabled));
+      self.update_locked(&mut state, MenuUpdate::SetTitle(format!("{} …", title)))
+    } else if let Some((title, enabled)) = state.loading.remove(&self.id) {
+      self.update_locked(&mut state, MenuUpdate::SetTitle(title))?;
+      self.update_locked(&mut state, MenuUpdate::SetEnabled(enabled))
+    } else {
+      Ok(())
+    }
   }
 
   /// Modifies the selected state of the menu item.
   pub fn set_selected(&self, selected: bool) -> crate::Result<()> {
-    self
-      .tray_handler
-      .update_item(self.id, MenuUpdate::SetSelected(selected))
-      .map_err(Into::into)
+    self.update(MenuUpdate::SetSelected(selected))
   }
 
   #[cfg(target_os = "macos")]
   #[cfg_attr(doc_cfg, doc(cfg(target_os = "macos")))]
   pub fn set_native_image(&self, image: crate::NativeImage) -> crate::Result<()> {
-    self
-      .tray_handler
-      .update_item(self.id, MenuUpdate::SetNativeImage(image))
-      .map_err(Into::into)
+    self.update(MenuUpdate::SetNativeImage(image))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{format_progress_title, QuitAction, SystemTrayMenu, TrayIcon, TrayIconBuilder};
+  use crate::{
+    test::{mock_context, noop_assets, MockRuntime},
+    CustomMenuItem, SystemTray, SystemTraySubmenu,
+  };
+  use std::path::PathBuf;
+
+  const PNG: &[u8] = &[0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+  const ICO: &[u8] = &[0, 0, 1, 0];
+
+  #[cfg(target_os = "linux")]
+  thread_local! {
+    /// The icon files of the test, removed when the test thread exits.
+    static ICON_DIR: tempfile::TempDir = tempfile::tempdir().unwrap();
+  }
+
+  /// Builds a distinct icon, of the variant and image format the platform accepts.
+  fn icon(seed: u8) -> TrayIcon {
+    let mut bytes = if cfg!(windows) { ICO } else { PNG }.to_vec();
+    bytes.push(seed);
+    // not built with `TrayIconBuilder`, whose Linux files are only removed when the app exits
+    #[cfg(target_os = "linux")]
+    return ICON_DIR.with(|dir| {
+      let path = dir.path().join(format!("icon-{}.png", seed));
+      std::fs::write(&path, bytes).unwrap();
+      TrayIcon::File(path)
+    });
+    #[cfg(not(target_os = "linux"))]
+    TrayIcon::Raw(bytes)
+  }
+
+  /// Removes the file written by [`TrayIconBuilder::from_bytes`].
+  #[cfg(target_os = "linux")]
+  fn remove_icon_file(icon: &TrayIcon) {
+    if let TrayIcon::File(path) = icon {
+      std::fs::remove_file(path).unwrap();
+    }
+  }
+
+  /// The `(icon_path, icon_bytes)` snapshot fields of the icon.
+  fn icon_fields(icon: TrayIcon) -> (Option<PathBuf>, Option<Vec<u8>>) {
+    #[allow(unreachable_patterns)]
+    match icon {
+      TrayIcon::File(path) => (Some(path), None),
+      TrayIcon::Raw(bytes) => (None, Some(bytes)),
+      _ => unreachable!(),
+    }
+  }
+
+  pub(crate) fn mock_tray_app(menu: SystemTrayMenu) -> crate::App<MockRuntime> {
+    crate::Builder::<MockRuntime>::new()
+      .system_tray(SystemTray::new().with_icon(icon(1)).with_menu(menu))
+      .build(mock_context(noop_assets()))
+      .unwrap()
+  }
+
+  #[test]
+  fn reassert() {
+    let app = mock_tray_app(SystemTrayMenu::new().add_item(CustomMenuItem::new("quit", "Quit")));
+    let tray = app.tray_handle();
+    tray.get_item("quit").set_title("Exit").unwrap();
+    tray.inner.calls.lock().unwrap().clear();
+
+    // simulates the taskbar being recreated
+    tray.reassert().unwrap();
+    assert_eq!(
+      *tray.inner.calls.lock().unwrap(),
+      vec!["set_icon", "set_menu"]
+    );
+
+    let state = tray.state.lock().unwrap();
+    match &state.menu.as_ref().unwrap().items[0] {
+      super::SystemTrayMenuEntry::CustomItem(item) => assert_eq!(item.title, "Exit"),
+      _ => panic!("unexpected menu entry"),
+    }
+  }
+
+  #[test]
+  fn failed_update_keeps_cache() {
+    use std::sync::atomic::Ordering;
+
+    let app = mock_tray_app(SystemTrayMenu::new().add_item(CustomMenuItem::new("quit", "Quit")));
+    let tray = app.tray_handle();
+    let snapshot = tray.snapshot();
+    tray.inner.fail.store(true, Ordering::SeqCst);
+
+    assert!(tray.set_icon(icon(2)).is_err());
+    assert!(tray
+      .set_menu(SystemTrayMenu::new().add_item(CustomMenuItem::new("open", "Open")))
+      .is_err());
+    assert!(tray.get_item("quit").set_title("Exit").is_err());
+    assert_eq!(tray.snapshot(), snapshot);
+    assert!(tray.try_get_item("quit").is_some());
+    assert!(tray.try_get_item("open").is_none());
+
+    tray.inner.fail.store(false, Ordering::SeqCst);
+    tray.set_icon(icon(2)).unwrap();
+    assert_ne!(tray.snapshot(), snapshot);
+  }
+
+  #[test]
+  fn progress_title() {
+    for (fraction, expected) in [
+      (0.0, "Download — 0%"),
+      (0.424, "Download — 42%"),
+      (1.0, "Download — 100%"),
+      (1.5, "Download — 100%"),
+      (-0.3, "Download — 0%"),
+      (f64::NAN, "Download — 0%"),
+    ] {
+      assert_eq!(format_progress_title("Download", fraction), expected);
+    }
+  }
+
+  #[test]
+  fn tray_icon_builder() {
+    let dir = tempfile::tempdir().unwrap();
+    let image = if cfg!(windows) { ICO } else { PNG };
+    let image_path = dir.path().join("icon");
+    std::fs::write(&image_path, image).unwrap();
+    let svg = dir.path().join("icon.svg");
+    std::fs::write(&svg, "<svg></svg>").unwrap();
+
+    let from_path = TrayIconBuilder::from_path(&image_path).build().unwrap();
+    let from_bytes = TrayIconBuilder::from_bytes(image).build().unwrap();
+    let from_svg = TrayIconBuilder::from_svg(&svg).build();
+
+    #[cfg(target_os = "linux")]
+    {
+      assert!(matches!(from_path, TrayIcon::File(p) if p == image_path));
+      assert!(
+        matches!(&from_bytes, TrayIcon::File(p) if p.is_file() && p.extension().unwrap() == "png")
+      );
+      remove_icon_file(&from_bytes);
+      assert!(matches!(from_svg, Ok(TrayIcon::File(p)) if p == svg));
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+      assert!(matches!(from_path, TrayIcon::Raw(b) if b == image));
+      assert!(matches!(from_bytes, TrayIcon::Raw(b) if b == image));
+      assert!(from_svg.is_err());
+    }
+
+    assert!(TrayIconBuilder::from_path(dir.path().join("missing.png"))
+      .build()
+      .is_err());
+    assert!(TrayIconBuilder::from_bytes(Vec::new()).build().is_err());
+  }
+
+  #[test]
+  fn tray_icon_format() {
+    use super::IconFormat;
+
+    assert_eq!(IconFormat::sniff(PNG), Some(IconFormat::Png));
+    assert_eq!(IconFormat::sniff(ICO), Some(IconFormat::Ico));
+    assert_eq!(
+      IconFormat::sniff(b"<?xml version=\"1.0\"?><svg></svg>"),
+      Some(IconFormat::Svg)
+    );
+    assert_eq!(IconFormat::sniff(b"GIF89a"), None);
+
+    let is_invalid =
+      |result: crate::Result<TrayIcon>| matches!(result, Err(crate::Error::InvalidTrayIcon { .. }));
+    assert!(is_invalid(
+      TrayIconBuilder::from_bytes(&b"GIF89a"[..]).build()
+    ));
+    // the platform format is checked, not the file extension
+    let dir = tempfile::tempdir().unwrap();
+    let gif = dir.path().join("icon.png");
+    std::fs::write(&gif, b"GIF89a").unwrap();
+    assert!(is_invalid(TrayIconBuilder::from_path(&gif).build()));
+    let not_svg = dir.path().join("icon.svg");
+    std::fs::write(&not_svg, PNG).unwrap();
+    assert!(is_invalid(TrayIconBuilder::from_svg(&not_svg).build()));
+
+    #[cfg(windows)]
+    assert!(is_invalid(TrayIconBuilder::from_bytes(PNG).build()));
+    #[cfg(target_os = "macos")]
+    assert!(is_invalid(TrayIconBuilder::from_bytes(ICO).build()));
+    #[cfg(target_os = "linux")]
+    {
+      let ico = TrayIconBuilder::from_bytes(ICO).build().unwrap();
+      assert!(matches!(&ico, TrayIcon::File(p) if p.extension().unwrap() == "ico"));
+      remove_icon_file(&ico);
+    }
+  }
+
+  #[test]
+  fn loading() {
+    let app = mock_tray_app(SystemTrayMenu::new().add_item(CustomMenuItem::new("sync", "Sync")));
+    let tray = app.tray_handle();
+    let item = tray.get_item("sync");
+    let item_state = || {
+      let state = tray.state.lock().unwrap();
+      let item = state.item(item.id).unwrap();
+      (item.title.clone(), item.enabled)
+    };
+
+    item.set_loading(true).unwrap();
+    assert_eq!(item_state(), ("Sync …".into(), false));
+    // toggling twice must not lose the original title
+    item.set_loading(true).unwrap();
+    assert_eq!(item_state(), ("Sync …".into(), false));
+
+    item.set_loading(false).unwrap();
+    assert_eq!(item_state(), ("Sync".into(), true));
+
+    tray.remove_item("sync").unwrap();
+    assert!(matches!(
+      item.set_loading(true),
+      Err(crate::Error::MenuItemNotFound(id)) if id == "sync"
+    ));
+  }
+
+  #[test]
+  fn icon_cache() {
+    let app = mock_tray_app(SystemTrayMenu::new());
+    let tray = app.tray_handle();
+    let set_icon_calls = || {
+      tray
+        .inner
+        .calls
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|c| **c == "set_icon")
+        .count()
+    };
+
+    tray.set_icon(icon(12)).unwrap();
+    tray.set_icon(icon(12)).unwrap();
+    assert_eq!(set_icon_calls(), 1);
+
+    tray.set_icon(icon(3)).unwrap();
+    assert_eq!(set_icon_calls(), 2);
+
+    tray.clear_icon_cache();
+    tray.set_icon(icon(3)).unwrap();
+    assert_eq!(set_icon_calls(), 3);
+  }
+
+  #[test]
+  fn quit_item() {
+    use std::sync::{
+      atomic::{AtomicBool, Ordering},
+      Arc, Mutex,
+    };
+
+    let app = mock_tray_app(SystemTrayMenu::new().add_item(CustomMenuItem::new("quit", "Quit")));
+    let tray = app.tray_handle();
+    assert!(matches!(
+      tray.set_quit_item("missing".into(), None),
+      Err(crate::Error::MenuItemNotFound(id)) if id == "missing"
+    ));
+
+    // simulates a click with a mock dialog that gives the `answer`
+    let click = |id: &str, answer: bool| {
+      let exited = Arc::new(AtomicBool::new(false));
+      let exited_ = exited.clone();
+      let asked = Arc::new(Mutex::new(None));
+      let asked_ = asked.clone();
+      super::on_quit_click(
+        &tray.state,
+        id,
+        move || exited_.store(true, Ordering::SeqCst),
+        move |message, on_answer| {
+          asked_.lock().unwrap().replace(message);
+          on_answer(answer);
+        },
+      );
+      let asked = asked.lock().unwrap().take();
+      (asked, exited.load(Ordering::SeqCst))
+    };
+    assert_eq!(click("quit", true), (None, false));
+
+    tray.set_quit_item("quit".into(), None).unwrap();
+    assert_eq!(click("quit", true), (None, true));
+    assert_eq!(click("other", true), (None, false));
+
+    #[cfg(feature = "dialog")]
+    {
+      tray
+        .set_quit_item("quit".into(), Some("Are you sure?".into()))
+        .unwrap();
+      assert_eq!(
+        tray.state.lock().unwrap().quit_action("quit"),
+        Some(QuitAction::Confirm("Are you sure?".into()))
+      );
+      assert_eq!(click("quit", false), (Some("Are you sure?".into()), false));
+      assert_eq!(click("quit", true), (Some("Are you sure?".into()), true));
+    }
+    #[cfg(not(feature = "dialog"))]
+    assert!(tray
+      .set_quit_item("quit".into(), Some("Are you sure?".into()))
+      .is_err());
+  }
+
+  #[test]
+  fn snapshot_restore() {
+    let app = mock_tray_app(
+      SystemTrayMenu::new()
+        .add_item(CustomMenuItem::new("sync", "Sync"))
+        .add_submenu(SystemTraySubmenu::new(
+          "More",
+          SystemTrayMenu::new().add_item(CustomMenuItem::new("debug", "Debug")),
+        )),
+    );
+    let tray = app.tray_handle();
+    let snapshot = tray.snapshot();
+    assert_eq!(
+      (snapshot.icon_path.clone(), snapshot.icon_bytes.clone()),
+      icon_fields(icon(1))
+    );
+    assert_eq!(snapshot.items.len(), 2);
+
+    tray.set_icon(icon(2)).unwrap();
+    tray.get_item("sync").set_title("Syncing").unwrap();
+    tray.get_item("debug").set_selected(true).unwrap();
+    assert_ne!(tray.snapshot(), snapshot);
+
+    tray.restore(snapshot.clone()).unwrap();
+    assert_eq!(tray.snapshot(), snapshot);
+
+    // items removed since the snapshot are skipped
+    tray.remove_item("debug").unwrap();
+    tray.get_item("sync").set_title("Syncing").unwrap();
+    tray.restore(snapshot).unwrap();
+    assert_eq!(tray.snapshot().items[0].title, "Sync");
+  }
+
+  #[test]
+  fn status_controller() {
+    use super::{TrayItemSnapshot, TrayStatus, TrayStatusController};
+
+    #[derive(Debug, PartialEq)]
+    enum Status {
+      Connecting,
+      Connected,
+    }
+
+    impl TrayStatus for Status {
+      fn icon(&self) -> TrayIcon {
+        match self {
+          Self::Connecting => icon(1),
+          Self::Connected => icon(2),
+        }
+      }
+
+      fn items(&self) -> Vec<TrayItemSnapshot> {
+        vec![TrayItemSnapshot {
+          id: "connect".into(),
+          title: "Disconnect".into(),
+          enabled: *self == Self::Connected,
+          selected: false,
+        }]
+      }
+    }
+
+    let app = mock_tray_app(
+      SystemTrayMenu::new()
+        .add_item(CustomMenuItem::new("connect", "Connect"))
+        .add_item(CustomMenuItem::new("quit", "Quit")),
+    );
+    let tray = app.tray_handle();
+    let mut controller = TrayStatusController::new(tray.clone());
+    let calls = || {
+      tray
+        .inner
+        .calls
+        .lock()
+        .unwrap()
+        .drain(..)
+        .collect::<Vec<_>>()
+    };
+
+    // the initial icon is already displayed, only the item title and enabled state change
+    controller.set_state(Status::Connecting).unwrap();
+    assert_eq!(calls(), vec!["update_item", "update_item"]);
+
+    controller.set_state(Status::Connected).unwrap();
+    assert_eq!(calls(), vec!["set_icon", "update_item"]);
+    assert_eq!(controller.state(), Some(&Status::Connected));
+
+    controller.set_state(Status::Connected).unwrap();
+    assert!(calls().is_empty());
+
+    let snapshot = tray.snapshot();
+    assert_eq!(
+      (snapshot.icon_path.clone(), snapshot.icon_bytes.clone()),
+      icon_fields(icon(2))
+    );
+    assert_eq!(snapshot.items[0].title, "Disconnect");
+    assert!(snapshot.items[0].enabled);
+    assert_eq!(snapshot.items[1].title, "Quit");
+  }
+
+  #[test]
+  fn with_busy() {
+    let app = mock_tray_app(
+      SystemTrayMenu::new()
+        .add_item(CustomMenuItem::new("sync", "Sync"))
+        .add_item(CustomMenuItem::new("quit", "Quit").disabled()),
+    );
+    let tray = app.tray_handle();
+    let enabled = |tray: &super::SystemTrayHandle<MockRuntime>| {
+      tray
+        .snapshot()
+        .items
+        .into_iter()
+        .map(|item| item.enabled)
+        .collect::<Vec<_>>()
+    };
+
+    let output = crate::async_runtime::block_on(tray.with_busy(|| async {
+      assert_eq!(enabled(&tray), vec![false, false]);
+      tray.get_item("sync").set_title("Sync — 50%").unwrap();
+      42
+    }))
+    .unwrap();
+    assert_eq!(output, 42);
+    assert_eq!(enabled(&tray), vec![true, false]);
+    // changes made while busy are kept
+    assert_eq!(tray.snapshot().items[0].title, "Sync — 50%");
+
+    let tray_ = tray.clone();
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+      crate::async_runtime::block_on(tray_.with_busy(|| async { panic!("operation failed") }))
+    }));
+    assert!(result.is_err());
+    assert_eq!(enabled(&tray), vec![true, false]);
+
+    let displayed = || {
+      let snapshot = tray.snapshot();
+      (snapshot.icon_path, snapshot.icon_bytes)
+    };
+    crate::async_runtime::block_on(tray.with_busy_spinner(
+      vec![icon(20), icon(21)],
+      std::time::Duration::from_secs(3600),
+      || async {
+        // lets the spinner set the first frame
+        tokio::task::yield_now().await;
+        assert_eq!(displayed(), icon_fields(icon(20)));
+      },
+    ))
+    .unwrap();
+    assert_eq!(displayed(), icon_fields(icon(1)));
+    assert_eq!(enabled(&tray), vec![true, false]);
+  }
+
+  #[cfg(windows)]
+  #[test]
+  fn destroy() {
+    let app = mock_tray_app(SystemTrayMenu::new().add_item(CustomMenuItem::new("quit", "Quit")));
+    let tray = app.tray_handle();
+    let item = tray.get_item("quit");
+    tray.destroy().unwrap();
+
+    assert!(matches!(
+      tray.set_menu(SystemTrayMenu::new()),
+      Err(crate::Error::SystemTrayDestroyed)
+    ));
+    assert!(matches!(
+      tray.set_icon(icon(2)),
+      Err(crate::Error::SystemTrayDestroyed)
+    ));
+    assert!(matches!(
+      item.set_enabled(false),
+      Err(crate::Error::SystemTrayDestroyed)
+    ));
+    assert!(tray.destroy().is_err());
+  }
+
+  #[test]
+  fn insert_remove_item() {
+    use super::SystemTrayMenuEntry;
+
+    let app = mock_tray_app(
+      SystemTrayMenu::new()
+        .add_item(CustomMenuItem::new("open", "Open"))
+        .add_submenu(SystemTraySubmenu::new(
+          "More",
+          SystemTrayMenu::new().add_item(CustomMenuItem::new("debug", "Debug")),
+        )),
+    );
+    let tray = app.tray_handle();
+    let ids = |tray: &super::SystemTrayHandle<MockRuntime>| {
+      tray
+        .snapshot()
+        .items
+        .into_iter()
+        .map(|item| item.id)
+        .collect::<Vec<_>>()
+    };
+
+    tray
+      .insert_item(
+        SystemTrayMenuEntry::CustomItem(CustomMenuItem::new("sync", "Sync")),
+        1,
+      )
+      .unwrap();
+    tray
+      .insert_item(
+        SystemTrayMenuEntry::CustomItem(CustomMenuItem::new("quit", "Quit")),
+        usize::MAX,
+      )
+      .unwrap();
+    assert_eq!(ids(&tray), vec!["open", "sync", "debug", "quit"]);
+    tray.get_item("sync").set_title("Syncing").unwrap();
+
+    tray.remove_item("debug").unwrap();
+    tray.remove_item("missing").unwrap();
+    assert_eq!(ids(&tray), vec!["open", "sync", "quit"]);
+    assert!(!tray.ids.lock().unwrap().values().any(|id| id == "debug"));
+
+    tray
+      .insert_submenu_item(
+        "More",
+        SystemTrayMenuEntry::CustomItem(CustomMenuItem::new("logs", "Logs")),
+        0,
+      )
+      .unwrap();
+    assert_eq!(ids(&tray), vec!["open", "sync", "logs", "quit"]);
+    tray.get_item("logs").set_enabled(false).unwrap();
+    assert!(matches!(
+      tray.insert_submenu_item(
+        "Missing",
+        SystemTrayMenuEntry::CustomItem(CustomMenuItem::new("other", "Other")),
+        0,
+      ),
+      Err(crate::Error::MenuItemNotFound(title)) if title == "Missing"
+    ));
+  }
+
+  #[test]
+  fn recent_items() {
+    let app = mock_tray_app(
+      SystemTrayMenu::new()
+        .add_item(CustomMenuItem::new("open", "Open"))
+        .add_submenu(SystemTraySubmenu::new("Recent", SystemTrayMenu::new()))
+        .add_item(CustomMenuItem::new("quit", "Quit")),
+    );
+    let tray = app.tray_handle();
+    let ids = || {
+      tray
+        .snapshot()
+        .items
+        .into_iter()
+        .map(|item| item.id)
+        .collect::<Vec<_>>()
+    };
+
+    let recent = tray.recent_items("Recent", 3);
+    for file in ["a", "b", "c", "d"] {
+      recent.push(file, format!("{}.txt", file)).unwrap();
+    }
+    assert_eq!(ids(), vec!["open", "d", "c", "b", "quit"]);
+    assert!(!tray.ids.lock().unwrap().values().any(|id| id == "a"));
+
+    // pushing an item again moves it to the top
+    recent.push("b", "b.txt").unwrap();
+    recent.push("e", "e.txt").unwrap();
+    assert_eq!(ids(), vec!["open", "e", "b", "d", "quit"]);
+    tray.get_item("e").set_enabled(false).unwrap();
+
+    assert!(matches!(
+      tray.recent_items("Missing", 3).push("a", "a.txt"),
+      Err(crate::Error::MenuItemNotFound(title)) if title == "Missing"
+    ));
+  }
+
+  #[test]
+  fn item_ids() {
+    let app = mock_tray_app(
+      SystemTrayMenu::new()
+        .add_item(CustomMenuItem::new("open", "Open"))
+        .add_submenu(SystemTraySubmenu::new(
+          "More",
+          SystemTrayMenu::new().add_item(CustomMenuItem::new("quit", "Quit")),
+        )),
+    );
+    let tray = app.tray_handle();
+
+    let mut items = tray.items();
+    items.sort();
+    assert_eq!(items, vec!["open", "quit"]);
+    assert_eq!(tray.get_item("quit").id(), "quit");
+  }
+
+  #[test]
+  fn try_get_item() {
+    let app = mock_tray_app(SystemTrayMenu::new().add_item(CustomMenuItem::new("quit", "Quit")));
+    let tray = app.tray_handle();
+    assert!(tray.try_get_item("quit").is_some());
+    assert!(tray.try_get_item("missing").is_none());
+
+    tray.set_menu(SystemTrayMenu::new()).unwrap();
+    assert!(tray.try_get_item("quit").is_none());
+  }
+
+  #[test]
+  fn geometry() {
+    use super::{PhysicalPosition, PhysicalSize, RuntimeSystemTrayEvent};
+
+    let app = mock_tray_app(SystemTrayMenu::new().add_item(CustomMenuItem::new("open", "Open")));
+    let tray = app.tray_handle();
+    assert_eq!(tray.geometry().unwrap(), None);
+
+    let position = PhysicalPosition::new(1200.0, 0.0);
+    let size = PhysicalSize::new(24.0, 24.0);
+    super::on_tray_event(
+      &app.handle(),
+      &tray.ids,
+      &tray.state,
+      &RuntimeSystemTrayEvent::LeftClick { position, size },
+    );
+    assert_eq!(tray.geometry().unwrap(), Some((position, size)));
+  }
+
+  #[test]
+  fn update_batch() {
+    use super::MenuUpdate;
+    use std::sync::atomic::Ordering;
+
+    let app = mock_tray_app(
+      SystemTrayMenu::new()
+        .add_item(CustomMenuItem::new("sync", "Sync"))
+        .add_item(CustomMenuItem::new("quit", "Quit")),
+    );
+    let tray = app.tray_handle();
+    tray.inner.calls.lock().unwrap().clear();
+
+    assert!(matches!(
+      tray.update_batch(vec![
+        ("sync".into(), MenuUpdate::SetEnabled(false)),
+        ("missing".into(), MenuUpdate::SetEnabled(false)),
+      ]),
+      Err(crate::Error::MenuItemNotFound(id)) if id == "missing"
+    ));
+    assert!(tray.snapshot().items[0].enabled);
+
+    tray
+      .update_batch(vec![
+        ("sync".into(), MenuUpdate::SetEnabled(false)),
+        ("sync".into(), MenuUpdate::SetTitle("Syncing".into())),
+        ("quit".into(), MenuUpdate::SetSelected(true)),
+      ])
+      .unwrap();
+    assert_eq!(
+      *tray.inner.calls.lock().unwrap(),
+      vec!["update_item", "update_item", "update_item"]
+    );
+    let items = tray.snapshot().items;
+    assert_eq!(
+      (items[0].title.as_str(), items[0].enabled),
+      ("Syncing", false)
+    );
+    assert!(items[1].selected);
+
+    tray.inner.fail.store(true, Ordering::SeqCst);
+    assert!(tray
+      .update_batch(vec![("sync".into(), MenuUpdate::SetEnabled(true))])
+      .is_err());
+    tray.inner.fail.store(false, Ordering::SeqCst);
+    assert!(!tray.snapshot().items[0].enabled);
+  }
+
+  #[test]
+  fn invalid_tray_icon() {
+    let app = mock_tray_app(SystemTrayMenu::new());
+    let tray = app.tray_handle();
+    #[cfg(target_os = "linux")]
+    let wrong = TrayIcon::Raw(vec![1]);
+    #[cfg(not(target_os = "linux"))]
+    let wrong = TrayIcon::File("icon.png".into());
+
+    match tray.set_icon(wrong) {
+      Err(crate::Error::InvalidTrayIcon { expected, got }) => assert_ne!(expected, got),
+      _ => panic!("expected an invalid tray icon error"),
+    }
+    assert!(tray.set_icon(icon(2)).is_ok());
   }
 }
@@ -13,18 +13,56 @@ use tauri_macros::{module_command_handler, CommandModule};
 pub enum Cmd {
   /// The get CLI matches API.
   CliMatches,
+  /// The get CLI subcommand matches API.
+  CliSubcommandMatches { name: String },
+  /// The get CLI matches of the given arguments API.
+  CliMatchesFrom { args: Vec<String> },
+}
+
+/// Parses the arguments with the CLI definition. The first argument is the binary name.
+#[cfg(cli)]
+fn get_matches<R, I, T>(
+  context: &InvokeContext<R>,
+  args: I,
+) -> super::Result<crate::api::cli::Matches>
+where
+  R: Runtime,
+  I: IntoIterator<Item = T>,
+  T: Into<std::ffi::OsString> + Clone,
+{
+  #[cfg(dev)]
+  if let Some(cli) = crate::api::cli::reloaded_config() {
+    return crate::api::cli::get_matches_from(&cli, &context.package_info, args)
+      .map_err(Into::into);
+  }
+  if let Some(cli) = &context.config.tauri.cli {
+    crate::api::cli::get_matches_from(cli, &context.package_info, args).map_err(Into::into)
+  } else {
+    Err(crate::Error::ApiNotAllowlisted("CLI definition not set under tauri.conf.json > tauri > cli (https://tauri.studio/docs/api/config#tauri.cli)".into()).into_anyhow())
+  }
 }
 
 impl Cmd {
   #[module_command_handler(cli, "CLI definition not set under tauri.conf.json > tauri > cli (https://tauri.studio/docs/api/config#tauri.cli)")]
   fn cli_matches<R: Runtime>(context: InvokeContext<R>) -> super::Result<InvokeResponse> {
-    if let Some(cli) = &context.config.tauri.cli {
-      crate::api::cli::get_matches(cli, &context.package_info)
-        .map(Into::into)
-        .map_err(Into::into)
-    } else {
-      Err(crate::Error::ApiNotAllowlisted("CLI definition not set under tauri.conf.json > tauri > cli (https://tauri.studio/docs/api/config#tauri.cli)".into()).into_anyhow())
-    }
+    get_matches(&context, std::env::args_os()).map(Into::into)
+  }
+
+  #[module_command_handler(cli, "CLI definition not set under tauri.conf.json > tauri > cli (https://tauri.studio/docs/api/config#tauri.cli)")]
+  fn cli_subcommand_matches<R: Runtime>(
+    context: InvokeContext<R>,
+    name: String,
+  ) -> super::Result<InvokeResponse> {
+    let matches = get_matches(&context, std::env::args_os())?;
+    Ok(serde_json::json!({ "subcommandMatches": matches.find_subcommand(&name) }).into())
+  }
+
+  #[module_command_handler(cli, "CLI definition not set under tauri.conf.json > tauri > cli (https://tauri.studio/docs/api/config#tauri.cli)")]
+  fn cli_matches_from<R: Runtime>(
+    context: InvokeContext<R>,
+    args: Vec<String>,
+  ) -> super::Result<InvokeResponse> {
+    get_matches(&context, args).map(Into::into)
   }
 }
 
@@ -36,4 +74,18 @@ mod tests {
     let res = super::Cmd::cli_matches(crate::test::mock_invoke_context());
     crate::test_utils::assert_not_allowlist_error(res);
   }
+
+  #[tauri_macros::module_command_test(cli, "CLI definition not set under tauri.conf.json > tauri > cli (https://tauri.studio/docs/api/config#tauri.cli)")]
+  #[quickcheck_macros::quickcheck]
+  fn cli_subcommand_matches(name: String) {
+    let res = super::Cmd::cli_subcommand_matches(crate::test::mock_invoke_context(), name);
+    crate::test_utils::assert_not_allowlist_error(res);
+  }
+
+  #[tauri_macros::module_command_test(cli, "CLI definition not set under tauri.conf.json > tauri > cli (https://tauri.studio/docs/api/config#tauri.cli)")]
+  #[quickcheck_macros::quickcheck]
+  fn cli_matches_from(args: Vec<String>) {
+    let res = super::Cmd::cli_matches_from(crate::test::mock_invoke_context(), args);
+    crate::test_utils::assert_not_allowlist_error(res);
+  }
 }
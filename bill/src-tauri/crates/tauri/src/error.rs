@@ -127,6 +127,23 @@ pub enum Error {
   /// Invalid glob pattern.
   #[error("invalid glob pattern: {0}")]
   GlobPattern(#[from] glob::PatternError),
+  /// The tray icon variant or image format is not one the platform accepts.
+  #[cfg(feature = "system-tray")]
+  #[error("invalid tray icon: expected {expected} but got {got}")]
+  InvalidTrayIcon {
+    /// The icon variant or image format the platform accepts.
+    expected: &'static str,
+    /// The icon variant or image format that was given.
+    got: &'static str,
+  },
+  /// The system tray menu has no item with the given id.
+  #[cfg(feature = "system-tray")]
+  #[error("tray menu item `{0}` not found")]
+  MenuItemNotFound(String),
+  /// The system tray was destroyed with [`crate::SystemTrayHandle::destroy`].
+  #[cfg(feature = "system-tray")]
+  #[error("the system tray was destroyed")]
+  SystemTrayDestroyed,
   /// Error decoding PNG image.
   #[cfg(feature = "icon-png")]
   #[error("failed to decode PNG: {0}")]
@@ -25,6 +25,7 @@
 //! - **system-tray**: Enables application system tray API. Enabled by default if the `systemTray` config is defined on the `tauri.conf.json` file.
 //! - **macos-private-api**: Enables features only available in **macOS**'s private APIs, currently the `transparent` window functionality and the `fullScreenEnabled` preference setting to `true`. Enabled by default if the `tauri > macosPrivateApi` config flag is set to `true` on the `tauri.conf.json` file.
 //! - **window-data-url**: Enables usage of data URLs on the webview.
+//! - **tracing**: Emits [`tracing`](https://docs.rs/tracing) events for the system tray updates and the notification deliveries.
 //!
 //! ## Cargo allowlist features
 //!
@@ -188,7 +189,10 @@ pub use runtime::{menu::NativeImage, ActivationPolicy};
 #[cfg(feature = "system-tray")]
 #[cfg_attr(doc_cfg, doc(cfg(feature = "system-tray")))]
 pub use {
-  self::app::tray::{SystemTrayEvent, SystemTrayHandle},
+  self::app::tray::{
+    RecentItems, SystemTrayEvent, SystemTrayHandle, TrayIconBuilder, TrayItemSnapshot,
+    TraySnapshot, TrayStatus, TrayStatusController,
+  },
   self::runtime::{
     menu::{SystemTrayMenu, SystemTrayMenuItem, SystemTraySubmenu},
     SystemTray,
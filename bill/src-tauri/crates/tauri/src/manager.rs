@@ -183,6 +183,8 @@ fn replace_csp_nonce(
 #[default_runtime(crate::Wry, wry)]
 pub struct InnerWindowManager<R: Runtime> {
   windows: Mutex<HashMap<String, Window<R>>>,
+  /// Labels of the windows that currently have focus.
+  focused_windows: Mutex<HashSet<String>>,
   pub(crate) plugins: Mutex<PluginStore<R>>,
   listeners: Listeners,
   pub(crate) state: Arc<StateManager>,
@@ -285,6 +287,7 @@ impl<R: Runtime> WindowManager<R> {
     Self {
       inner: Arc::new(InnerWindowManager {
         windows: Mutex::default(),
+        focused_windows: Mutex::default(),
         plugins: Mutex::new(plugins),
         listeners: Listeners::default(),
         state: Arc::new(state),
@@ -1188,6 +1191,30 @@ impl<R: Runtime> WindowManager<R> {
 
   pub(crate) fn on_window_close(&self, label: &str) {
     self.windows_lock().remove(label);
+    self.set_window_focused(label, false);
+  }
+
+  pub(crate) fn set_window_focused(&self, label: &str, focused: bool) {
+    let mut focused_windows = self
+      .inner
+      .focused_windows
+      .lock()
+      .expect("poisoned window manager");
+    if focused {
+      focused_windows.insert(label.into());
+    } else {
+      focused_windows.remove(label);
+    }
+  }
+
+  /// Whether any of the app windows currently has focus.
+  pub(crate) fn is_focused(&self) -> bool {
+    !self
+      .inner
+      .focused_windows
+      .lock()
+      .expect("poisoned window manager")
+      .is_empty()
   }
 
   pub fn emit_filter<S, F>(
@@ -1291,14 +1318,17 @@ fn on_window_event<R: Runtime>(
         ))?;
       }
     }
-    WindowEvent::Focused(focused) => window.emit(
-      if *focused {
-        WINDOW_FOCUS_EVENT
-      } else {
-        WINDOW_BLUR_EVENT
-      },
-      (),
-    )?,
+    WindowEvent::Focused(focused) => {
+      manager.set_window_focused(window.label(), *focused);
+      window.emit(
+        if *focused {
+          WINDOW_FOCUS_EVENT
+        } else {
+          WINDOW_BLUR_EVENT
+        },
+        (),
+      )?
+    }
     WindowEvent::ScaleFactorChanged {
       scale_factor,
       new_inner_size,
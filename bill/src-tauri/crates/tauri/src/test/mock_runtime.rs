@@ -26,6 +26,8 @@ use uuid::Uuid;
 #[cfg(windows)]
 use windows::Win32::Foundation::HWND;
 
+#[cfg(feature = "system-tray")]
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::{
   collections::HashMap,
   fmt,
@@ -488,18 +490,33 @@ impl<T: UserEvent> Dispatch<T> for MockDispatcher {
 #[derive(Debug, Clone)]
 pub struct MockTrayHandler {
   context: RuntimeContext,
+  /// The name of the tray operations applied to this handler.
+  pub calls: Arc<Mutex<Vec<&'static str>>>,
+  /// Makes the tray operations fail, e.g. to test error handling.
+  pub fail: Arc<AtomicBool>,
+}
+
+#[cfg(feature = "system-tray")]
+impl MockTrayHandler {
+  fn call(&self, name: &'static str) -> Result<()> {
+    if self.fail.load(Ordering::SeqCst) {
+      return Err(tauri_runtime::Error::FailedToSendMessage);
+    }
+    self.calls.lock().unwrap().push(name);
+    Ok(())
+  }
 }
 
 #[cfg(feature = "system-tray")]
 impl TrayHandle for MockTrayHandler {
   fn set_icon(&self, icon: TrayIcon) -> Result<()> {
-    Ok(())
+    self.call("set_icon")
   }
   fn set_menu(&self, menu: SystemTrayMenu) -> Result<()> {
-    Ok(())
+    self.call("set_menu")
   }
   fn update_item(&self, id: u16, update: MenuUpdate) -> Result<()> {
-    Ok(())
+    self.call("update_item")
   }
   #[cfg(target_os = "macos")]
   fn set_icon_as_template(&self, is_template: bool) -> Result<()> {
@@ -541,6 +558,8 @@ impl MockRuntime {
       #[cfg(feature = "system-tray")]
       tray_handler: MockTrayHandler {
         context: context.clone(),
+        calls: Default::default(),
+        fail: Default::default(),
       },
       context,
     }